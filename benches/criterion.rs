@@ -3,8 +3,8 @@ use std::fs::read_to_string;
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
 use aoc2020::{
-    day1, day10, day11, day12, day13, day14, day15, day16, day2, day3, day4, day5, day6, day7,
-    day8, day9, Runner,
+    day1, day10, day11, day12, day13, day14, day15, day16, day17, day2, day3, day4, day5, day6,
+    day7, day8, day9, Runner,
 };
 
 fn day01(c: &mut Criterion) {
@@ -281,6 +281,22 @@ fn day16(c: &mut Criterion) {
     group.finish();
 }
 
+fn day17(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day17");
+    let input = read_to_string(format!("input/2020/day{}.txt", day17::Day17::day())).unwrap();
+    group.bench_function("get_input", |b| {
+        b.iter(|| <day17::Day17 as Runner>::get_input(black_box(&input)))
+    });
+    let input = <day17::Day17 as Runner>::get_input(&input).unwrap();
+    group.bench_function("part1", |b| {
+        b.iter(|| <day17::Day17 as Runner>::part1(black_box(&input)))
+    });
+    group.bench_function("part2", |b| {
+        b.iter(|| <day17::Day17 as Runner>::part2(black_box(&input)))
+    });
+    group.finish();
+}
+
 criterion_group!(
     benches,
     day01,
@@ -300,5 +316,6 @@ criterion_group!(
     day14,
     day15,
     day16,
+    day17,
 );
 criterion_main!(benches);
@@ -0,0 +1,101 @@
+use super::{Automaton, Cell, Coord, Neighborhood, Offsets};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dense<S, const N: usize> {
+    dims: [usize; N],
+    cells: Vec<S>,
+    next: Vec<S>,
+    adjacency: Vec<Vec<usize>>,
+}
+
+impl<S: Cell, const N: usize> Dense<S, N> {
+    pub fn new(dims: [usize; N], cells: Vec<S>) -> Self {
+        assert_eq!(dims.iter().product::<usize>(), cells.len());
+        Self {
+            dims,
+            next: cells.clone(),
+            adjacency: Vec::new(),
+            cells,
+        }
+        .with_neighborhood(&Offsets::moore())
+    }
+
+    pub fn with_neighborhood<Nb: Neighborhood<N>>(mut self, neighborhood: &Nb) -> Self {
+        self.adjacency = (0..self.cells.len())
+            .map(|idx| {
+                let mut adjacent = Vec::new();
+                neighborhood.neighbors(&self.coord(idx), &mut |n| {
+                    if let Some(n) = self.index(&n) {
+                        adjacent.push(n);
+                    }
+                });
+                adjacent
+            })
+            .collect();
+        self
+    }
+
+    pub fn dims(&self) -> [usize; N] {
+        self.dims
+    }
+
+    pub fn index(&self, coord: &Coord<N>) -> Option<usize> {
+        coord
+            .iter()
+            .zip(&self.dims)
+            .rev()
+            .try_fold(0, |idx, (&c, &dim)| {
+                if c < 0 || c as usize >= dim {
+                    None
+                } else {
+                    Some(idx * dim + c as usize)
+                }
+            })
+    }
+
+    pub fn coord(&self, mut idx: usize) -> Coord<N> {
+        let mut coord = [0; N];
+        coord.iter_mut().zip(&self.dims).for_each(|(c, &dim)| {
+            *c = (idx % dim) as i32;
+            idx /= dim;
+        });
+        coord
+    }
+
+    pub fn get(&self, coord: &Coord<N>) -> Option<S> {
+        self.index(coord).map(|idx| self.cells[idx])
+    }
+
+    pub fn cells(&self) -> &[S] {
+        &self.cells
+    }
+}
+
+impl<S: Cell, const N: usize> Automaton for Dense<S, N> {
+    type Cell = S;
+
+    fn step<R>(&mut self, rule: &R) -> usize
+    where
+        R: Fn(S, usize) -> S,
+    {
+        let cells = &self.cells;
+        let mut changes = 0;
+        self.next
+            .iter_mut()
+            .zip(cells)
+            .zip(&self.adjacency)
+            .for_each(|((next, &cell), adjacent)| {
+                let count = adjacent.iter().filter(|&&n| cells[n].is_active()).count();
+                *next = rule(cell, count);
+                if *next != cell {
+                    changes += 1;
+                }
+            });
+        std::mem::swap(&mut self.cells, &mut self.next);
+        changes
+    }
+
+    fn count(&self, cell: S) -> usize {
+        self.cells.iter().filter(|&&c| c == cell).count()
+    }
+}
@@ -0,0 +1,173 @@
+mod dense;
+mod sparse;
+
+pub use self::dense::Dense;
+pub use self::sparse::Sparse;
+
+pub type Coord<const N: usize> = [i32; N];
+
+pub trait Cell: Copy + Eq + Default {
+    fn is_active(&self) -> bool;
+}
+
+impl Cell for bool {
+    fn is_active(&self) -> bool {
+        *self
+    }
+}
+
+pub trait Neighborhood<const N: usize> {
+    fn neighbors(&self, coord: &Coord<N>, f: &mut dyn FnMut(Coord<N>));
+}
+
+impl<F, const N: usize> Neighborhood<N> for F
+where
+    F: Fn(&Coord<N>) -> Vec<Coord<N>>,
+{
+    fn neighbors(&self, coord: &Coord<N>, f: &mut dyn FnMut(Coord<N>)) {
+        self(coord).into_iter().for_each(f)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Offsets<const N: usize>(Vec<Coord<N>>);
+
+impl<const N: usize> Offsets<N> {
+    pub fn new(offsets: Vec<Coord<N>>) -> Self {
+        Self(offsets)
+    }
+
+    pub fn moore() -> Self {
+        Self(
+            (0..3usize.pow(N as u32))
+                .map(|mut i| {
+                    let mut offset = [0; N];
+                    offset.iter_mut().for_each(|o| {
+                        *o = (i % 3) as i32 - 1;
+                        i /= 3;
+                    });
+                    offset
+                })
+                .filter(|offset| offset.iter().any(|&o| o != 0))
+                .collect(),
+        )
+    }
+
+    pub fn von_neumann() -> Self {
+        Self(
+            (0..N)
+                .flat_map(|axis| {
+                    [-1, 1].iter().map(move |&d| {
+                        let mut offset = [0; N];
+                        offset[axis] = d;
+                        offset
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<const N: usize> Neighborhood<N> for Offsets<N> {
+    fn neighbors(&self, coord: &Coord<N>, f: &mut dyn FnMut(Coord<N>)) {
+        self.0.iter().for_each(|offset| {
+            let mut n = *coord;
+            n.iter_mut().zip(offset).for_each(|(c, o)| *c += o);
+            f(n)
+        })
+    }
+}
+
+pub trait Automaton {
+    type Cell: Cell;
+
+    fn step<R>(&mut self, rule: &R) -> usize
+    where
+        R: Fn(Self::Cell, usize) -> Self::Cell;
+
+    fn count(&self, cell: Self::Cell) -> usize;
+
+    fn run<R>(&mut self, rule: &R, generations: usize) -> &mut Self
+    where
+        R: Fn(Self::Cell, usize) -> Self::Cell,
+    {
+        (0..generations).for_each(|_| {
+            self.step(rule);
+        });
+        self
+    }
+
+    fn settle<R>(&mut self, rule: &R) -> usize
+    where
+        R: Fn(Self::Cell, usize) -> Self::Cell,
+    {
+        let mut generations = 0;
+        while self.step(rule) != 0 {
+            generations += 1;
+        }
+        generations
+    }
+}
+
+pub fn conway<'a>(alive: &'a [usize], born: &'a [usize]) -> impl Fn(bool, usize) -> bool + 'a {
+    move |cell, count| {
+        if cell {
+            alive.contains(&count)
+        } else {
+            born.contains(&count)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets() {
+        assert_eq!(8, Offsets::<2>::moore().len());
+        assert_eq!(26, Offsets::<3>::moore().len());
+        assert_eq!(80, Offsets::<4>::moore().len());
+        assert_eq!(6, Offsets::<3>::von_neumann().len());
+    }
+
+    #[test]
+    fn blinker() {
+        let rule = conway(&[2, 3], &[3]);
+        let mut dense = Dense::new(
+            [5, 5],
+            (0..25)
+                .map(|i| i / 5 == 2 && i % 5 > 0 && i % 5 < 4)
+                .collect(),
+        );
+        let mut sparse = Sparse::new(Offsets::moore());
+        (1..4).for_each(|x| sparse.insert([x, 2], true));
+
+        assert_eq!(4, dense.step(&rule));
+        assert_eq!(4, sparse.step(&rule));
+        assert_eq!(Some(true), dense.get(&[2, 1]));
+        assert_eq!(Some(false), dense.get(&[1, 2]));
+        assert!(sparse.get(&[2, 3]));
+        assert!(!sparse.get(&[3, 2]));
+
+        dense.run(&rule, 9);
+        sparse.run(&rule, 9);
+        assert_eq!(Some(true), dense.get(&[3, 2]));
+        assert!(sparse.get(&[1, 2]));
+        assert_eq!(3, dense.count(true));
+        assert_eq!(3, sparse.count(true));
+
+        let mut plus = Dense::new([3, 3], (0..9).map(|i| i == 4).collect())
+            .with_neighborhood(&Offsets::von_neumann());
+        assert_eq!(5, plus.step(&conway(&[], &[1])));
+        assert_eq!(Some(false), plus.get(&[0, 0]));
+    }
+}
@@ -0,0 +1,90 @@
+use std::{collections::HashMap, hash::BuildHasherDefault};
+
+use hashers::fx_hash::FxHasher;
+
+use super::{Automaton, Cell, Coord, Neighborhood};
+
+type Map<K, V> = HashMap<K, V, BuildHasherDefault<FxHasher>>;
+
+// Neighbour counts are gathered by scattering from the active cells, so the neighbourhood
+// must be symmetric, and `rule(S::default(), 0)` must stay the default.
+#[derive(Debug, Clone)]
+pub struct Sparse<S, Nb, const N: usize> {
+    cells: Map<Coord<N>, S>,
+    neighborhood: Nb,
+}
+
+impl<S: Cell, Nb: Neighborhood<N>, const N: usize> Sparse<S, Nb, N> {
+    pub fn new(neighborhood: Nb) -> Self {
+        Self {
+            cells: Map::default(),
+            neighborhood,
+        }
+    }
+
+    pub fn insert(&mut self, coord: Coord<N>, cell: S) {
+        if cell == S::default() {
+            self.cells.remove(&coord);
+        } else {
+            self.cells.insert(coord, cell);
+        }
+    }
+
+    pub fn get(&self, coord: &Coord<N>) -> S {
+        self.cells.get(coord).copied().unwrap_or_default()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Coord<N>, &S)> {
+        self.cells.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
+
+impl<S: Cell, Nb: Neighborhood<N>, const N: usize> Automaton for Sparse<S, Nb, N> {
+    type Cell = S;
+
+    fn step<R>(&mut self, rule: &R) -> usize
+    where
+        R: Fn(S, usize) -> S,
+    {
+        let mut counts: Map<Coord<N>, usize> = Map::default();
+        counts.reserve(self.cells.len());
+        self.cells.keys().for_each(|&coord| {
+            counts.entry(coord).or_insert(0);
+        });
+        self.cells
+            .iter()
+            .filter(|(_, cell)| cell.is_active())
+            .for_each(|(coord, _)| {
+                self.neighborhood
+                    .neighbors(coord, &mut |n| *counts.entry(n).or_insert(0) += 1)
+            });
+
+        let mut changes = 0;
+        let mut next = Map::default();
+        next.reserve(self.cells.len());
+        for (coord, count) in counts {
+            let cell = self.get(&coord);
+            let new = rule(cell, count);
+            if new != cell {
+                changes += 1;
+            }
+            if new != S::default() {
+                next.insert(coord, new);
+            }
+        }
+        self.cells = next;
+        changes
+    }
+
+    fn count(&self, cell: S) -> usize {
+        self.cells.values().filter(|&&c| c == cell).count()
+    }
+}
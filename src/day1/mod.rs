@@ -1,13 +1,209 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use hashers::fx_hash::FxHasher;
 use itertools::Itertools;
-use std::collections::HashSet;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    hash::BuildHasherDefault,
+};
 
 use crate::Runner;
 
+type Map<K, V> = HashMap<K, V, BuildHasherDefault<FxHasher>>;
+
+pub const TARGET: i64 = 2020;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    Sorted,
+    MeetInTheMiddle,
+}
+
+#[derive(Debug, Clone)]
+pub struct KSum<'a> {
+    nums: Cow<'a, [i32]>,
+    k: usize,
+    target: i64,
+    strategy: Strategy,
+}
+
+impl<'a> KSum<'a> {
+    pub fn new(nums: &'a [i32], k: usize, target: i64) -> Self {
+        let strategy = if k >= 4 {
+            Strategy::MeetInTheMiddle
+        } else {
+            Strategy::Sorted
+        };
+        let nums = if nums.windows(2).all(|w| w[0] <= w[1]) {
+            Cow::Borrowed(nums)
+        } else {
+            Cow::Owned(nums.iter().copied().sorted().collect())
+        };
+        Self {
+            nums,
+            k,
+            target,
+            strategy,
+        }
+    }
+
+    pub fn with_strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn first(&self) -> Option<Vec<i32>> {
+        self.search(1).pop()
+    }
+
+    pub fn all(&self) -> Vec<Vec<i32>> {
+        let mut solutions = self.search(usize::MAX);
+        solutions.sort_unstable();
+        solutions
+    }
+
+    fn search(&self, limit: usize) -> Vec<Vec<i32>> {
+        let mut out = Vec::new();
+        match self.strategy {
+            Strategy::Sorted => sorted(
+                &self.nums,
+                self.k,
+                self.target,
+                &mut vec![],
+                &mut out,
+                limit,
+            ),
+            Strategy::MeetInTheMiddle => {
+                meet_in_the_middle(&self.nums, self.k, self.target, &mut out, limit)
+            }
+        }
+        out
+    }
+}
+
+fn sorted(
+    nums: &[i32],
+    k: usize,
+    target: i64,
+    prefix: &mut Vec<i32>,
+    out: &mut Vec<Vec<i32>>,
+    limit: usize,
+) {
+    if k == 0 {
+        if target == 0 && out.len() < limit {
+            out.push(prefix.clone());
+        }
+        return;
+    }
+    if nums.len() < k {
+        return;
+    }
+    let min: i64 = nums[..k].iter().map(|&n| n as i64).sum();
+    let max: i64 = nums[nums.len() - k..].iter().map(|&n| n as i64).sum();
+    if target < min || target > max {
+        return;
+    }
+
+    match k {
+        1 => {
+            if let Some(n) = i32::try_from(target)
+                .ok()
+                .filter(|n| nums.binary_search(n).is_ok())
+            {
+                prefix.push(n);
+                out.push(prefix.clone());
+                prefix.pop();
+            }
+        }
+        2 => {
+            let (mut i, mut j) = (0, nums.len() - 1);
+            while i < j && out.len() < limit {
+                let sum = nums[i] as i64 + nums[j] as i64;
+                if sum == target {
+                    out.push(prefix.iter().copied().chain([nums[i], nums[j]]).collect());
+                    while i < j && nums[i] == nums[i + 1] {
+                        i += 1;
+                    }
+                    i += 1;
+                    j -= 1;
+                } else if sum < target {
+                    i += 1;
+                } else {
+                    j -= 1;
+                }
+            }
+        }
+        _ => {
+            for i in 0..=nums.len() - k {
+                if out.len() >= limit {
+                    return;
+                }
+                if i > 0 && nums[i] == nums[i - 1] {
+                    continue;
+                }
+                prefix.push(nums[i]);
+                sorted(
+                    &nums[i + 1..],
+                    k - 1,
+                    target - nums[i] as i64,
+                    prefix,
+                    out,
+                    limit,
+                );
+                prefix.pop();
+            }
+        }
+    }
+}
+
+fn meet_in_the_middle(nums: &[i32], k: usize, target: i64, out: &mut Vec<Vec<i32>>, limit: usize) {
+    if k == 0 {
+        if target == 0 && limit > 0 {
+            out.push(vec![]);
+        }
+        return;
+    }
+    let sum = |combo: &[usize]| combo.iter().map(|&i| nums[i] as i64).sum::<i64>();
+    let left = k / 2;
+    let mut halves: Map<i64, Vec<Vec<usize>>> = Map::default();
+    for combo in (0..nums.len()).combinations(left) {
+        halves.entry(sum(&combo)).or_default().push(combo);
+    }
+
+    let mut seen = HashSet::new();
+    for right in (0..nums.len()).combinations(k - left) {
+        let lefts = match halves.get(&(target - sum(&right))) {
+            Some(lefts) => lefts,
+            None => continue,
+        };
+        for left in lefts.iter().filter(|l| l.last() < right.first()) {
+            let solution = left
+                .iter()
+                .chain(&right)
+                .map(|&i| nums[i])
+                .collect::<Vec<_>>();
+            if seen.insert(solution.clone()) {
+                out.push(solution);
+                if out.len() >= limit {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn product(input: &[i32], k: usize) -> Result<i32> {
+    let solution = KSum::new(input, k, TARGET)
+        .first()
+        .with_context(|| format!("no {} entries sum to {}", k, TARGET))?;
+    Ok(solution.iter().product())
+}
+
 pub struct Day01;
 
 impl Runner for Day01 {
-    type Input = HashSet<i32>;
+    type Input = Vec<i32>;
     type Output = i32;
 
     fn day() -> usize {
@@ -15,31 +211,68 @@ impl Runner for Day01 {
     }
 
     fn get_input(input: &str) -> Result<Self::Input> {
-        let nums = input
+        let mut nums = input
             .lines()
             .map(&str::trim)
-            .map(|l| l.parse().unwrap())
-            .collect();
+            .map(|l| l.parse().with_context(|| format!("invalid entry `{}`", l)))
+            .collect::<Result<Vec<_>>>()?;
+        nums.sort_unstable();
         Ok(nums)
     }
 
     fn part1(input: &Self::Input) -> Result<Self::Output> {
-        let mut products = input
-            .iter()
-            .map(|&a| (a, 2020 - a))
-            .filter(|(_, b)| input.contains(&b))
-            .map(|(a, b)| a * b);
-        Ok(products.next().unwrap())
+        product(input, 2)
     }
 
     fn part2(input: &Self::Input) -> Result<Self::Output> {
-        let mut products = input
-            .iter()
-            .tuple_combinations()
-            .filter(|(&a, &b)| a + b < 2020)
-            .map(|(&a, &b)| (a, b, 2020 - a - b))
-            .filter(|(_, _, c)| input.contains(&c))
-            .map(|(a, b, c)| a * b * c);
-        Ok(products.next().unwrap())
+        product(input, 3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Lcg;
+
+    #[test]
+    fn sample1() -> Result<()> {
+        let input = Day01::get_input("1721\n979\n366\n299\n675\n1456")?;
+        assert_eq!(514579, Day01::part1(&input)?);
+        assert_eq!(241861950, Day01::part2(&input)?);
+
+        let input = vec![1010, 5, 1010, 1010];
+        assert_eq!(vec![vec![1010, 1010]], KSum::new(&input, 2, TARGET).all());
+        assert!(KSum::new(&input, 3, 3030).first().is_some());
+        assert!(KSum::new(&input, 4, 4040).first().is_none());
+        assert_eq!(vec![Vec::<i32>::new()], KSum::new(&input, 0, 0).all());
+        assert!(Day01::get_input("1010\n1O10").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn strategies() {
+        let mut nums = Lcg(0x1234_5678)
+            .take(40)
+            .map(|r| (r % 50) as i32 - 10)
+            .collect::<Vec<_>>();
+        nums.sort_unstable();
+        for k in 0..=5 {
+            for target in [0, 17, 60] {
+                let search = KSum::new(&nums, k, target);
+                let all = search.clone().with_strategy(Strategy::Sorted).all();
+                assert_eq!(
+                    all,
+                    search
+                        .clone()
+                        .with_strategy(Strategy::MeetInTheMiddle)
+                        .all()
+                );
+                assert_eq!(all.iter().unique().count(), all.len());
+                assert!(all
+                    .iter()
+                    .all(|s| s.iter().map(|&n| n as i64).sum::<i64>() == target));
+                assert_eq!(all.is_empty(), search.first().is_none());
+            }
+        }
     }
 }
@@ -1,21 +1,50 @@
 use anyhow::Result;
+use hashers::fx_hash::FxHasher;
 use itertools::iproduct;
-use ndarray::{Array, Array2};
-use std::cell::RefCell;
-use std::iter::FromIterator;
+use ndarray::{par_azip, Array2};
+use rayon::prelude::*;
+use std::{
+    collections::HashMap,
+    fs::read_to_string,
+    hash::{BuildHasherDefault, Hash, Hasher},
+    path::Path,
+};
 use vec_map::VecMap;
 
-use crate::Runner;
+use crate::{
+    automaton::{self, Automaton, Cell, Dense},
+    grid::{Boundary, Grid2D},
+    Runner,
+};
+
+mod render;
+mod rules;
+
+pub use self::render::{export, frames, write_gif, Export, Frame, Stats};
+pub use self::rules::Rules;
 
 type Coord = (usize, usize);
+type Map<K, V> = HashMap<K, V, BuildHasherDefault<FxHasher>>;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Tile {
+    #[default]
     Floor,
     Empty,
     Full,
 }
 
+impl Tile {
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '.' => Some(Self::Floor),
+            'L' => Some(Self::Empty),
+            '#' => Some(Self::Full),
+            _ => None,
+        }
+    }
+}
+
 impl std::fmt::Display for Tile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -26,97 +55,270 @@ impl std::fmt::Display for Tile {
     }
 }
 
+impl Cell for Tile {
+    fn is_active(&self) -> bool {
+        *self == Tile::Full
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Settled {
+    Stable { generation: usize },
+    Oscillating { period: usize, generation: usize },
+}
+
+#[derive(Debug, Default)]
+pub struct CycleDetector {
+    seen: Map<u64, Vec<(usize, Vec<Tile>)>>,
+}
+
+impl CycleDetector {
+    pub fn observe(
+        &mut self,
+        generation: usize,
+        changes: usize,
+        tiles: &[Tile],
+    ) -> Option<Settled> {
+        if generation > 0 && changes == 0 {
+            return Some(Settled::Stable { generation });
+        }
+        // Equal hashes only nominate a candidate, the stored tiles decide
+        let states = self.seen.entry(hash_tiles(tiles)).or_default();
+        if let Some((previous, _)) = states.iter().find(|(_, seen)| seen[..] == *tiles) {
+            return Some(Settled::Oscillating {
+                period: generation - previous,
+                generation,
+            });
+        }
+        states.push((generation, tiles.to_vec()));
+        None
+    }
+}
+
+fn settle_with<R, T, S>(room: &mut R, tiles: T, mut step: S) -> Settled
+where
+    T: Fn(&R) -> &[Tile],
+    S: FnMut(&mut R) -> usize,
+{
+    let mut detector = CycleDetector::default();
+    detector.observe(0, 0, tiles(room));
+    (1..)
+        .find_map(|generation| {
+            let changes = step(room);
+            detector.observe(generation, changes, tiles(room))
+        })
+        .unwrap()
+}
+
+fn hash_tiles<'a, I: IntoIterator<Item = &'a Tile>>(tiles: I) -> u64 {
+    let mut hasher = FxHasher::default();
+    tiles.into_iter().for_each(|tile| tile.hash(&mut hasher));
+    hasher.finish()
+}
+
 #[derive(Debug, Clone)]
 pub struct Room {
-    width: usize,
-    height: usize,
-    tiles: Vec<Tile>,
-    neighbors_cache: RefCell<VecMap<Vec<usize>>>,
+    grid: Grid2D<Tile>,
+    next: Vec<Tile>,
+    starts: Vec<usize>,
+    adjacent: Vec<usize>,
+    active: Option<Vec<usize>>,
+    rules: Rules,
 }
 
 impl Room {
-    pub fn get(&self, (x, y): Coord) -> Option<Tile> {
-        if x >= self.width || y >= self.height {
-            return None;
+    pub fn new(grid: Grid2D<Tile>) -> Self {
+        Self {
+            next: grid.cells().to_vec(),
+            starts: Vec::new(),
+            adjacent: Vec::new(),
+            active: None,
+            rules: Rules::adjacent(),
+            grid,
         }
-        Some(self.tiles[self.get_coord(x, y)])
+        .with_rules(Rules::adjacent())
+    }
+
+    pub fn get(&self, (x, y): Coord) -> Option<Tile> {
+        self.grid.get((x as isize, y as isize)).copied()
     }
 
     pub fn get_coord(&self, x: usize, y: usize) -> usize {
-        (y * self.width) + x
+        self.grid.index((x, y))
+    }
+
+    pub fn grid(&self) -> &Grid2D<Tile> {
+        &self.grid
+    }
+
+    pub fn with_boundary(mut self, boundary: Boundary) -> Self {
+        self.grid.set_boundary(boundary);
+        self
+    }
+
+    fn resolve_all(&self, (x, y): Coord, coords: Vec<(isize, isize)>) -> Vec<usize> {
+        coords
+            .into_iter()
+            .filter_map(|coord| self.grid.resolve(coord))
+            .filter(|&coord| coord != (x, y))
+            .map(|coord| self.grid.index(coord))
+            .collect()
     }
 
-    pub fn neighbors<F>(&self, (x, y): Coord, coord_fn: F) -> *const Vec<usize>
+    fn adjacency<F>(&self, coord_fn: F) -> (Vec<usize>, Vec<usize>)
     where
         F: Fn(&Room, Coord) -> Vec<usize>,
     {
-        let mut cache = self.neighbors_cache.borrow_mut();
-        if cache.contains_key(self.get_coord(x, y)) {
-            return &cache[self.get_coord(x, y)];
+        let mut starts = Vec::with_capacity(self.grid.len() + 1);
+        let mut adjacent = Vec::with_capacity(self.grid.len() * 8);
+        starts.push(0);
+        for idx in 0..self.grid.len() {
+            adjacent.extend(coord_fn(self, self.grid.coord(idx)));
+            starts.push(adjacent.len());
         }
-        let v = coord_fn(self, (x, y));
-        cache.insert(self.get_coord(x, y), v);
-        &cache[self.get_coord(x, y)]
+        (starts, adjacent)
+    }
+
+    pub fn with_neighbors<F>(mut self, coord_fn: F) -> Self
+    where
+        F: Fn(&Room, Coord) -> Vec<usize>,
+    {
+        let (starts, adjacent) = self.adjacency(coord_fn);
+        self.starts = starts;
+        self.adjacent = adjacent;
+        self
+    }
+
+    pub fn neighbors(&self, (x, y): Coord) -> &[usize] {
+        let idx = self.get_coord(x, y);
+        &self.adjacent[self.starts[idx]..self.starts[idx + 1]]
     }
 
-    pub fn local_neighbors(&self, (x, y): Coord) -> Vec<usize> {
-        iproduct!((-1isize..=1), (-1isize..=1))
+    pub fn with_rules(self, rules: Rules) -> Self {
+        let mut room = self
+            .with_boundary(rules.boundary)
+            .with_neighbors(|room, coord| room.rule_neighbors(coord, &rules));
+        room.rules = rules;
+        room
+    }
+
+    pub fn rule_neighbors(&self, (x, y): Coord, rules: &Rules) -> Vec<usize> {
+        let coords = rules.neighbors((x as isize, y as isize), self.grid.len(), |coord| {
+            self.grid.get(coord).copied()
+        });
+        self.resolve_all((x, y), coords)
+    }
+
+    pub fn local_neighbors(&self, coord: Coord) -> Vec<usize> {
+        let offsets = iproduct!((-1isize..=1), (-1isize..=1))
             .filter(|&(a, b)| a != 0 || b != 0)
-            .map(move |(a, b)| (a + x as isize, b + y as isize))
-            .filter(move |&(x, y)| {
-                x >= 0 && y >= 0 && x < self.width as isize && y < self.height as isize
-            })
-            .map(|coord| self.get_coord(coord.0 as usize, coord.1 as usize))
+            .collect::<Vec<_>>();
+        self.grid
+            .neighbors(coord, &offsets)
+            .map(|coord| self.grid.index(coord))
             .collect()
     }
 
     pub fn extended_neighbors(&self, (x, y): Coord) -> Vec<usize> {
-        iproduct!((-1isize..=1), (-1isize..=1))
+        let coords = iproduct!((-1isize..=1), (-1isize..=1))
             .filter(|&(a, b)| a != 0 || b != 0)
             .filter_map(move |(a, b)| {
-                let coord = (1..)
+                (1..=self.grid.len() as isize)
                     .map(|i| (x as isize + (a * i), y as isize + (b * i)))
-                    .take_while(|&(x, y)| {
-                        x >= 0 && y >= 0 && x < self.width as isize && y < self.height as isize
-                    })
-                    .find(|&(x, y)| self.get((x as usize, y as usize)) != Some(Tile::Floor));
-                coord.map(|coord| self.get_coord(coord.0 as usize, coord.1 as usize))
+                    .take_while(|&coord| self.grid.get(coord).is_some())
+                    .find(|&coord| self.grid.get(coord) != Some(&Tile::Floor))
             })
-            .collect()
+            .collect();
+        self.resolve_all((x, y), coords)
     }
 
-    pub fn step<F>(&mut self, coord_fn: F, crowd: usize) -> usize
-    where
-        F: Fn(&Room, Coord) -> Vec<usize> + Copy,
-    {
-        let changes = iproduct!((0..self.width), (0..self.height))
-            .map(|coord| {
-                let neighbors = self.neighbors(coord, coord_fn);
-                (
-                    coord,
-                    self.get(coord),
-                    unsafe { neighbors.as_ref().unwrap() }
-                        .iter()
-                        .map(|&coord| self.tiles[coord])
-                        .filter(|&state| state == Tile::Full)
-                        .count(),
-                )
+    pub fn with_active_set(mut self) -> Self {
+        self.active = Some((0..self.grid.len()).collect());
+        self
+    }
+
+    fn full_neighbors(&self, idx: usize) -> usize {
+        self.adjacent[self.starts[idx]..self.starts[idx + 1]]
+            .iter()
+            .filter(|&&n| self.grid.cells()[n] == Tile::Full)
+            .count()
+    }
+
+    pub fn step(&mut self) -> usize {
+        if self.rules.dynamic_sight() {
+            let (starts, adjacent) =
+                self.adjacency(|room, coord| room.rule_neighbors(coord, &room.rules));
+            self.starts = starts;
+            self.adjacent = adjacent;
+            if self.active.is_some() {
+                self.active = Some((0..self.grid.len()).collect());
+            }
+        }
+        match self.active.take() {
+            Some(active) => self.step_active(&active),
+            None => self.step_all(),
+        }
+    }
+
+    fn step_all(&mut self) -> usize {
+        let mut next = std::mem::take(&mut self.next);
+        next.resize(self.grid.len(), Tile::Floor);
+        let this = &*self;
+        let changes = next
+            .par_chunks_mut(self.grid.width().max(1))
+            .enumerate()
+            .map(|(y, row)| {
+                row.iter_mut()
+                    .enumerate()
+                    .map(|(x, next)| {
+                        let idx = this.get_coord(x, y);
+                        let tile = this.grid.cells()[idx];
+                        *next = this.rules.apply(tile, this.full_neighbors(idx));
+                        (*next != tile) as usize
+                    })
+                    .sum::<usize>()
             })
-            .filter_map(|(coord, state, count)| match (state, count) {
-                (Some(Tile::Empty), 0) => Some((coord, Tile::Full)),
-                (Some(Tile::Full), x) if x >= crowd => Some((coord, Tile::Empty)),
-                _ => None,
+            .sum();
+        self.next = std::mem::replace(self.grid.cells_mut(), next);
+        changes
+    }
+
+    fn step_active(&mut self, active: &[usize]) -> usize {
+        let changes = active
+            .par_iter()
+            .filter_map(|&idx| {
+                let tile = self.grid.cells()[idx];
+                let next = self.rules.apply(tile, self.full_neighbors(idx));
+                if next != tile {
+                    Some((idx, next))
+                } else {
+                    None
+                }
             })
             .collect::<Vec<_>>();
-        changes.iter().for_each(|&(coord, state)| {
-            let coord = self.get_coord(coord.0, coord.1);
-            self.tiles[coord] = state;
-        });
+
+        let mut marked = vec![false; self.grid.len()];
+        let mut next_active = Vec::new();
+        for &(idx, tile) in &changes {
+            self.grid.cells_mut()[idx] = tile;
+            let neighbors = &self.adjacent[self.starts[idx]..self.starts[idx + 1]];
+            for &n in std::iter::once(&idx).chain(neighbors) {
+                if !marked[n] {
+                    marked[n] = true;
+                    next_active.push(n);
+                }
+            }
+        }
+        self.active = Some(next_active);
         changes.len()
     }
 
+    pub fn settle(&mut self) -> Settled {
+        settle_with(self, |room| room.grid.cells(), Room::step)
+    }
+
     pub fn count_tiles(&self, tile: Tile) -> usize {
-        self.tiles.iter().filter(|&t| *t == tile).count()
+        self.grid.cells().iter().filter(|&t| *t == tile).count()
     }
 }
 
@@ -134,122 +336,126 @@ fn print_room(room: &<Day11 as Runner>::Input) {
 
 pub struct Day11Unsafe;
 pub struct Day11;
+pub struct Day11Automaton;
 
 fn neighbors<'a>(
     room: &'a <Day11 as Runner>::Input,
     (x, y): (isize, isize),
+    rules: &Rules,
     cache: &'a mut VecMap<Vec<(usize, usize)>>,
 ) -> &'a Vec<Coord> {
     let dim = room.dim();
-    if cache.contains_key(x as usize * dim.0 + y as usize) {
-        return &cache[&(x as usize * dim.0 + y as usize)];
-    }
-    let v = iproduct!((-1..=1), (-1..=1))
-        .filter(|&(a, b)| a != 0 || b != 0)
-        .map(move |(a, b)| (a + x, b + y))
-        .filter(move |&(x, y)| x >= 0 && y >= 0 && x < dim.0 as isize && y < dim.1 as isize)
-        .map(|coord| (coord.0 as usize, coord.1 as usize))
-        .collect();
-    cache.insert(x as usize * dim.0 + y as usize, v);
-    &cache[&(x as usize * dim.0 + y as usize)]
+    let key = x as usize * dim.1 + y as usize;
+    if !cache.contains_key(key) {
+        let resolve = |coord| rules.boundary.resolve(coord, dim);
+        let v = rules
+            .neighbors((x, y), room.len(), |coord| {
+                resolve(coord).map(|coord| room[coord])
+            })
+            .into_iter()
+            .filter_map(resolve)
+            .filter(|&coord| coord != (x as usize, y as usize))
+            .collect();
+        cache.insert(key, v);
+    }
+    &cache[key]
 }
 
-fn extended_neighbors<'a>(
-    room: &'a <Day11 as Runner>::Input,
-    (x, y): (isize, isize),
-    cache: &'a mut VecMap<Vec<(usize, usize)>>,
-) -> &'a Vec<Coord> {
-    let dim = room.dim();
-    if cache.contains_key(x as usize * dim.0 + y as usize) {
-        return &cache[&(x as usize * dim.0 + y as usize)];
-    }
-    let v = iproduct!((-1..=1), (-1..=1))
-        .filter(|&(a, b)| a != 0 || b != 0)
-        .filter_map(move |(a, b)| {
-            let coord = (1..)
-                .map(|i| (x + (a * i), y + (b * i)))
-                .take_while(|&(x, y)| x >= 0 && y >= 0 && x < dim.0 as isize && y < dim.1 as isize)
-                .find(|&(x, y)| room[(x as usize, y as usize)] != Tile::Floor);
-            coord.map(|coord| (coord.0 as usize, coord.1 as usize))
-        })
-        .collect();
-    cache.insert(x as usize * dim.0 + y as usize, v);
-    &cache[&(x as usize * dim.0 + y as usize)]
+struct DoubleBuffer {
+    back: Array2<Tile>,
+    cache: VecMap<Vec<Coord>>,
 }
 
-fn step<F>(
-    room: &mut <Day11 as Runner>::Input,
-    neighbors_f: F,
-    crowd: usize,
-    cache: &mut VecMap<Vec<(usize, usize)>>,
-) -> usize
-where
-    F: for<'a> Fn(
-        &'a <Day11 as Runner>::Input,
-        (isize, isize),
-        &'a mut VecMap<Vec<(usize, usize)>>,
-    ) -> &'a Vec<Coord>,
-{
-    let dim = room.dim();
-    let changes = iproduct!((0..dim.0), (0..dim.1))
-        .map(|coord| {
-            (
-                coord,
-                room[coord],
-                neighbors_f(&room, (coord.0 as isize, coord.1 as isize), cache)
-                    .iter()
-                    .map(|&coord| room[coord])
-                    .filter(|&state| state == Tile::Full)
-                    .count(),
-            )
-        })
-        .filter_map(|(coord, state, count)| match (state, count) {
-            (Tile::Empty, 0) => Some((coord, Tile::Full)),
-            (Tile::Full, x) if x >= crowd => Some((coord, Tile::Empty)),
-            _ => None,
-        })
-        .collect::<Vec<_>>();
-    changes.iter().for_each(|&(coord, state)| {
-        room[coord] = state;
-    });
-    changes.len()
+impl DoubleBuffer {
+    fn new() -> Self {
+        Self {
+            back: Array2::from_elem((0, 0), Tile::Floor),
+            cache: VecMap::new(),
+        }
+    }
+
+    fn step(&mut self, room: &mut <Day11 as Runner>::Input, rules: &Rules) -> usize {
+        if rules.dynamic_sight() {
+            self.cache.clear();
+        }
+        if self.back.dim() != room.dim() {
+            self.back = room.clone();
+        }
+        let (width, height) = room.dim();
+        for (x, y) in iproduct!(0..width, 0..height) {
+            neighbors(room, (x as isize, y as isize), rules, &mut self.cache);
+        }
+
+        let (front, cache) = (&*room, &self.cache);
+        par_azip!((index (x, y), next in &mut self.back) {
+            let count = cache[x * height + y]
+                .iter()
+                .filter(|&&coord| front[coord] == Tile::Full)
+                .count();
+            *next = rules.apply(front[(x, y)], count);
+        });
+        std::mem::swap(room, &mut self.back);
+        room.iter()
+            .zip(self.back.iter())
+            .filter(|(a, b)| a != b)
+            .count()
+    }
+}
+
+pub fn occupied(room: &Room, rules: &Rules) -> usize {
+    let mut room = room.clone().with_rules(rules.clone());
+    room.settle();
+    room.count_tiles(Tile::Full)
+}
+
+pub fn run_rules(rules: &Rules, export_to: Option<(&Path, Export)>) -> Result<usize> {
+    let input = read_to_string(format!("input/2020/day{}.txt", Day11Unsafe::day()))?;
+    let room = Day11Unsafe::get_input(&input)?;
+    if let Some((dir, format)) = export_to {
+        let frames = frames(&room, rules);
+        export(&frames, dir, format)?;
+        return Ok(frames.last().unwrap().stats.occupied);
+    }
+    Ok(occupied(&room, rules))
+}
+
+fn occupied_array(room: &<Day11 as Runner>::Input, rules: &Rules) -> usize {
+    let mut buffer = DoubleBuffer::new();
+    let mut room = room.clone();
+    settle_with(
+        &mut room,
+        |room| room.as_slice_memory_order().unwrap(),
+        |room| buffer.step(room, rules),
+    );
+    room.into_iter().filter(|&t| *t == Tile::Full).count()
+}
+
+fn occupied_dense(room: &Room, rules: &Rules) -> usize {
+    let room = room.clone().with_rules(rules.clone());
+    let (width, height) = room.grid.dim();
+    let neighborhood = |c: &automaton::Coord<2>| {
+        room.neighbors((c[0] as usize, c[1] as usize))
+            .iter()
+            .map(|&idx| {
+                let (x, y) = room.grid.coord(idx);
+                [x as i32, y as i32]
+            })
+            .collect()
+    };
+    let mut grid =
+        Dense::new([width, height], room.grid.cells().to_vec()).with_neighborhood(&neighborhood);
+    grid.settle(&|tile, count| rules.apply(tile, count));
+    grid.count(Tile::Full)
 }
 
 fn parse_room(input: &str) -> Result<<Day11 as Runner>::Input> {
-    let height = input.lines().count();
-    let width = input.lines().next().unwrap().len();
-    let iter = input
-        .chars()
-        .filter(|c| !c.is_ascii_whitespace())
-        .map(|c| match c {
-            'L' => Tile::Empty,
-            '.' => Tile::Floor,
-            '#' => Tile::Full,
-            _ => unreachable!(),
-        });
-    Ok(Array::from_iter(iter)
-        .into_shape((height, width))?
-        .reversed_axes())
+    let grid = Grid2D::parse(input, Tile::from_char)?;
+    let (width, height) = grid.dim();
+    Ok(Array2::from_shape_vec((height, width), grid.into_cells())?.reversed_axes())
 }
 
 fn parse_room_unsafe(input: &str) -> Result<<Day11Unsafe as Runner>::Input> {
-    let height = input.lines().count();
-    let width = input.lines().next().unwrap().len();
-    let iter = input
-        .chars()
-        .filter(|c| !c.is_ascii_whitespace())
-        .map(|c| match c {
-            'L' => Tile::Empty,
-            '.' => Tile::Floor,
-            '#' => Tile::Full,
-            _ => unreachable!(),
-        });
-    Ok(Room {
-        width,
-        height,
-        tiles: iter.collect(),
-        neighbors_cache: RefCell::new(VecMap::new()),
-    })
+    Ok(Room::new(Grid2D::parse(input, Tile::from_char)?))
 }
 
 impl Runner for Day11 {
@@ -265,21 +471,11 @@ impl Runner for Day11 {
     }
 
     fn part1(input: &Self::Input) -> Result<Self::Output> {
-        let mut cache = VecMap::new();
-        let mut room = input.clone();
-        while 0 != step(&mut room, neighbors, 4, &mut cache) {
-            // print_room(&room);
-        }
-        Ok(room.into_iter().filter(|&t| *t == Tile::Full).count())
+        Ok(occupied_array(input, &Rules::adjacent()))
     }
 
     fn part2(input: &Self::Input) -> Result<Self::Output> {
-        let mut cache = VecMap::new();
-        let mut room = input.clone();
-        while 0 != step(&mut room, extended_neighbors, 5, &mut cache) {
-            // print_room(&room);
-        }
-        Ok(room.into_iter().filter(|&t| *t == Tile::Full).count())
+        Ok(occupied_array(input, &Rules::line_of_sight()))
     }
 }
 
@@ -292,7 +488,7 @@ impl Runner for Day11Unsafe {
     }
 
     fn comment() -> &'static str {
-        "UNSAFE"
+        "Room"
     }
 
     fn get_input(input: &str) -> Result<Self::Input> {
@@ -300,40 +496,58 @@ impl Runner for Day11Unsafe {
     }
 
     fn part1(input: &Self::Input) -> Result<Self::Output> {
-        let mut room = input.clone();
-        while 0 != room.step(Room::local_neighbors, 4) {
-            // print_room(&room);
-        }
-        Ok(room.count_tiles(Tile::Full))
+        Ok(occupied(input, &Rules::adjacent()))
     }
 
     fn part2(input: &Self::Input) -> Result<Self::Output> {
-        let mut room: Room = input.clone();
-        while 0 != room.step(Room::extended_neighbors, 5) {
-            // print_room(&room);
-        }
-        Ok(room.count_tiles(Tile::Full))
+        Ok(occupied(input, &Rules::line_of_sight()))
+    }
+}
+
+impl Runner for Day11Automaton {
+    type Input = Room;
+    type Output = usize;
+
+    fn day() -> usize {
+        11
+    }
+
+    fn comment() -> &'static str {
+        "Automaton"
+    }
+
+    fn get_input(input: &str) -> Result<Self::Input> {
+        parse_room_unsafe(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Output> {
+        Ok(occupied_dense(input, &Rules::adjacent()))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Output> {
+        Ok(occupied_dense(input, &Rules::line_of_sight()))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Lcg;
+
+    pub(super) const SAMPLE: &str = "L.LL.LL.LL\n\
+                                     LLLLLLL.LL\n\
+                                     L.L.L..L..\n\
+                                     LLLL.LL.LL\n\
+                                     L.LL.LL.LL\n\
+                                     L.LLLLL.LL\n\
+                                     ..L.L.....\n\
+                                     LLLLLLLLLL\n\
+                                     L.LLLLLL.L\n\
+                                     L.LLLLL.LL";
 
     #[test]
     fn sample2() -> Result<()> {
-        let input = "L.LL.LL.LL\n\
-                     LLLLLLL.LL\n\
-                     L.L.L..L..\n\
-                     LLLL.LL.LL\n\
-                     L.LL.LL.LL\n\
-                     L.LLLLL.LL\n\
-                     ..L.L.....\n\
-                     LLLLLLLLLL\n\
-                     L.LLLLLL.L\n\
-                     L.LLLLL.LL";
-
-        let input = Day11::get_input(input)?;
+        let input = Day11::get_input(SAMPLE)?;
         print_room(&input);
         let expected = "#.#L.L#.##\n\
                         #LLL#LL.L#\n\
@@ -346,7 +560,7 @@ mod tests {
                         #.LLLLLL.L\n\
                         #.#L#L#.##";
         let mut room = input.clone();
-        while step(&mut room, neighbors, 4, &mut VecMap::new()) != 0 {
+        while DoubleBuffer::new().step(&mut room, &Rules::adjacent()) != 0 {
             // println!("{:?}", room);
         }
         let expected = parse_room(expected)?;
@@ -367,19 +581,155 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn sample_room() -> Result<()> {
+        let input = Day11Unsafe::get_input(SAMPLE)?;
+        let room = input.clone().with_neighbors(Room::local_neighbors);
+        assert_eq!(&[10, 1, 11], room.neighbors((0, 0)));
+        assert_eq!(37, Day11Unsafe::part1(&input)?);
+        assert_eq!(26, Day11Unsafe::part2(&input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn custom_rules() -> Result<()> {
+        let room = Day11Unsafe::get_input(SAMPLE)?;
+        let array = Day11::get_input(SAMPLE)?;
+
+        let rules: Rules = "line_of_sight,range=1,survival=3".parse()?;
+        assert_eq!(37, occupied(&room, &rules));
+        let rules: Rules = "adjacent,range=inf,blocks=L#,survival=4".parse()?;
+        assert_eq!(26, occupied(&room, &rules));
+        assert_eq!(26, occupied_array(&array, &rules));
+
+        let rules: Rules = "survival=8".parse()?;
+        assert_eq!(room.count_tiles(Tile::Empty), occupied(&room, &rules));
+        Ok(())
+    }
+
+    #[test]
+    fn oscillation() -> Result<()> {
+        let rules: Rules = "survival=0".parse()?;
+        let mut room = Day11Unsafe::get_input("LL")?.with_rules(rules.clone());
+        assert_eq!(
+            Settled::Oscillating {
+                period: 2,
+                generation: 2
+            },
+            room.settle()
+        );
+        assert_eq!(0, occupied_array(&Day11::get_input("LL")?, &rules));
+
+        let mut room = Day11Unsafe::get_input("L.L\n...\nL.L")?.with_rules(rules);
+        assert_eq!(Settled::Stable { generation: 2 }, room.settle());
+        assert_eq!(4, room.count_tiles(Tile::Full));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_errors() -> Result<()> {
+        let room = Day11Unsafe::get_input("L.#\n#.L\n")?;
+        assert_eq!("L.#\n#.L", room.grid().to_string());
+        assert!(Day11::get_input("L.L\nLL").is_err());
+        assert!(Day11Unsafe::get_input("L.L\nLxL").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parsed_room_steps() -> Result<()> {
+        let mut room = Day11Unsafe::get_input("LLL\nLLL\nLLL")?;
+        assert_eq!(&[3, 1, 4], room.neighbors((0, 0)));
+        room.settle();
+        assert_eq!(4, room.count_tiles(Tile::Full));
+        Ok(())
+    }
+
+    #[test]
+    fn dynamic_sight() -> Result<()> {
+        let input = "LLLL\nLLLL\nLLLL\nLLLL";
+        let room = Day11Unsafe::get_input(input)?;
+        let array = Day11::get_input(input)?;
+        let rules: Rules = "line_of_sight,blocks=#".parse()?;
+        assert!(rules.dynamic_sight());
+        assert!(!Rules::line_of_sight().dynamic_sight());
+
+        let mut sight = room.clone().with_rules(rules.clone());
+        assert!(sight.neighbors((0, 0)).is_empty());
+        sight.step();
+        assert_eq!(16, sight.count_tiles(Tile::Full));
+        sight.step();
+        assert_eq!(4, sight.count_tiles(Tile::Full));
+        assert_eq!(3, sight.neighbors((0, 0)).len());
+
+        let expected = occupied(&room, &rules);
+        assert_ne!(16, expected);
+        assert_eq!(expected, occupied_array(&array, &rules));
+        let mut active = room.with_rules(rules).with_active_set();
+        active.settle();
+        assert_eq!(expected, active.count_tiles(Tile::Full));
+        Ok(())
+    }
+
+    #[test]
+    fn boundaries() -> Result<()> {
+        let input = "LLL\nLLL\nLLL";
+        let room = Day11Unsafe::get_input(input)?;
+        let array = Day11::get_input(input)?;
+        assert_eq!(4, occupied(&room, &Rules::adjacent()));
+
+        let rules: Rules = "boundary=wrap".parse()?;
+        let mut wrapped = room.clone().with_rules(rules.clone());
+        assert_eq!(8, wrapped.neighbors((0, 0)).len());
+        assert_eq!(
+            Settled::Oscillating {
+                period: 2,
+                generation: 2
+            },
+            wrapped.settle()
+        );
+        assert_eq!(occupied(&room, &rules), occupied_array(&array, &rules));
+
+        let rules: Rules = "boundary=reflect".parse()?;
+        let reflected = room.with_rules(rules);
+        assert_eq!(&[4, 1, 4, 3, 3, 4, 1, 4], reflected.neighbors((0, 0)));
+        Ok(())
+    }
+
+    #[test]
+    fn generated() -> Result<()> {
+        let mut rng = Lcg(0x2545_f491);
+        let input = (0..50)
+            .map(|_| {
+                rng.by_ref()
+                    .take(70)
+                    .map(|r| if r.is_multiple_of(5) { '.' } else { 'L' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let expected = Day11::get_input(&input)?;
+        let room = Day11Unsafe::get_input(&input)?;
+        assert_eq!(Day11::part1(&expected)?, Day11Unsafe::part1(&room)?);
+        assert_eq!(Day11::part2(&expected)?, Day11Unsafe::part2(&room)?);
+
+        let mut full = room.clone().with_rules(Rules::line_of_sight());
+        let mut active = full.clone().with_active_set();
+        loop {
+            let changes = full.step();
+            assert_eq!(changes, active.step());
+            assert_eq!(full.grid, active.grid);
+            if changes == 0 {
+                break;
+            }
+        }
+        assert!(active.active.unwrap().is_empty());
+        Ok(())
+    }
+
     #[test]
     fn all_steps() -> Result<()> {
-        let input = "L.LL.LL.LL\n\
-                     LLLLLLL.LL\n\
-                     L.L.L..L..\n\
-                     LLLL.LL.LL\n\
-                     L.LL.LL.LL\n\
-                     L.LLLLL.LL\n\
-                     ..L.L.....\n\
-                     LLLLLLLLLL\n\
-                     L.LLLLLL.L\n\
-                     L.LLLLL.LL";
-        let mut room = parse_room(input)?;
+        let mut room = parse_room(SAMPLE)?;
         let round1 = "#.##.##.##\n\
                       #######.##\n\
                       #.#.#..#..\n\
@@ -391,7 +741,7 @@ mod tests {
                       #.######.#\n\
                       #.#####.##";
         let round1 = parse_room(round1)?;
-        step(&mut room, neighbors, 4, &mut VecMap::new());
+        DoubleBuffer::new().step(&mut room, &Rules::adjacent());
 
         let dim = room.dim();
         for y in 0..dim.1 {
@@ -407,17 +757,7 @@ mod tests {
 
     #[test]
     fn all_steps2() -> Result<()> {
-        let input = "L.LL.LL.LL\n\
-                     LLLLLLL.LL\n\
-                     L.L.L..L..\n\
-                     LLLL.LL.LL\n\
-                     L.LL.LL.LL\n\
-                     L.LLLLL.LL\n\
-                     ..L.L.....\n\
-                     LLLLLLLLLL\n\
-                     L.LLLLLL.L\n\
-                     L.LLLLL.LL";
-        let mut room = parse_room(input)?;
+        let mut room = parse_room(SAMPLE)?;
         let round1 = "#.##.##.##\n\
                       #######.##\n\
                       #.#.#..#..\n\
@@ -429,7 +769,7 @@ mod tests {
                       #.######.#\n\
                       #.#####.##";
         let round1 = parse_room(round1)?;
-        step(&mut room, extended_neighbors, 5, &mut VecMap::new());
+        DoubleBuffer::new().step(&mut room, &Rules::line_of_sight());
         assert_eq!(room, round1);
 
         let round2 = "#.LL.LL.L#\n\
@@ -443,7 +783,7 @@ mod tests {
                       #.LLLLLL.L\n\
                       #.LLLLL.L#";
         let round2 = parse_room(round2)?;
-        step(&mut room, extended_neighbors, 5, &mut VecMap::new());
+        DoubleBuffer::new().step(&mut room, &Rules::line_of_sight());
 
         let dim = room.dim();
         for y in 0..dim.1 {
@@ -455,4 +795,27 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn automaton() -> Result<()> {
+        let room = parse_room_unsafe(SAMPLE)?;
+        let seats = |crowd| {
+            move |tile, count| match (tile, count) {
+                (Tile::Empty, 0) => Tile::Full,
+                (Tile::Full, x) if x >= crowd => Tile::Empty,
+                (tile, _) => tile,
+            }
+        };
+
+        let (width, height) = room.grid.dim();
+        let mut grid = Dense::new([width, height], room.grid.cells().to_vec());
+        grid.settle(&seats(4));
+        assert_eq!(37, grid.count(Tile::Full));
+
+        assert_eq!(37, Day11Automaton::part1(&room)?);
+        assert_eq!(26, Day11Automaton::part2(&room)?);
+        let rules: Rules = "survival=3,boundary=wrap".parse()?;
+        assert_eq!(occupied(&room, &rules), occupied_dense(&room, &rules));
+        Ok(())
+    }
 }
@@ -0,0 +1,250 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    fs::{create_dir_all, File},
+    io::{BufWriter, Write},
+    path::Path,
+    slice::Chunks,
+};
+
+use anyhow::{bail, Result};
+use gif::{Encoder, Repeat};
+
+use super::{CycleDetector, Room, Rules, Tile};
+
+const PALETTE: [u8; 9] = [40, 40, 40, 200, 200, 200, 200, 40, 40];
+
+fn palette_index(tile: Tile) -> u8 {
+    match tile {
+        Tile::Floor => 0,
+        Tile::Empty => 1,
+        Tile::Full => 2,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub generation: usize,
+    pub occupied: usize,
+    pub empty: usize,
+    pub changed: usize,
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "generation {}: {} occupied, {} empty, {} changed",
+            self.generation, self.occupied, self.empty, self.changed
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub stats: Stats,
+    width: usize,
+    height: usize,
+    tiles: Vec<Tile>,
+}
+
+impl Frame {
+    pub fn capture(room: &Room, generation: usize, changed: usize) -> Self {
+        Self {
+            stats: Stats {
+                generation,
+                occupied: room.count_tiles(Tile::Full),
+                empty: room.count_tiles(Tile::Empty),
+                changed,
+            },
+            width: room.grid().width(),
+            height: room.grid().height(),
+            tiles: room.grid().cells().to_vec(),
+        }
+    }
+
+    fn rows(&self) -> Chunks<'_, Tile> {
+        self.tiles.chunks(self.width.max(1))
+    }
+
+    pub fn write_ppm<W: Write>(&self, mut out: W, scale: usize) -> Result<()> {
+        if scale == 0 {
+            bail!("scale must be at least 1");
+        }
+        writeln!(out, "P6")?;
+        writeln!(out, "# {}", self.stats)?;
+        writeln!(out, "{} {}", self.width * scale, self.height * scale)?;
+        writeln!(out, "255")?;
+        for row in self.rows() {
+            let line = row
+                .iter()
+                .flat_map(|&tile| {
+                    let idx = palette_index(tile) as usize * 3;
+                    std::iter::repeat_n(&PALETTE[idx..idx + 3], scale)
+                })
+                .flatten()
+                .copied()
+                .collect::<Vec<u8>>();
+            for _ in 0..scale {
+                out.write_all(&line)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn indexed_pixels(&self, scale: usize) -> Vec<u8> {
+        self.rows()
+            .flat_map(|row| {
+                let line = row
+                    .iter()
+                    .flat_map(|&tile| std::iter::repeat_n(palette_index(tile), scale))
+                    .collect::<Vec<_>>();
+                std::iter::repeat_n(line, scale).flatten()
+            })
+            .collect()
+    }
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.stats)?;
+        for row in self.rows() {
+            row.iter().try_for_each(|tile| write!(f, "{}", tile))?;
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn frames(room: &Room, rules: &Rules) -> Vec<Frame> {
+    let mut room = room.clone().with_rules(rules.clone());
+    let mut detector = CycleDetector::default();
+    detector.observe(0, 0, room.grid().cells());
+    let mut frames = vec![Frame::capture(&room, 0, 0)];
+    loop {
+        let changed = room.step();
+        if changed == 0 {
+            break;
+        }
+        let generation = frames.len();
+        frames.push(Frame::capture(&room, generation, changed));
+        if detector
+            .observe(generation, changed, room.grid().cells())
+            .is_some()
+        {
+            break;
+        }
+    }
+    frames
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Export {
+    Ascii,
+    Ppm { scale: usize },
+    Gif { scale: usize, delay: u16 },
+}
+
+pub fn write_gif<W: Write>(frames: &[Frame], out: W, scale: usize, delay: u16) -> Result<()> {
+    let first = match frames.first() {
+        Some(first) => first,
+        None => bail!("no frames to encode"),
+    };
+    let (width, height) = (first.width * scale, first.height * scale);
+    if width == 0 || height == 0 {
+        bail!("cannot encode a {}x{} GIF", width, height);
+    }
+    if width > u16::MAX as usize || height > u16::MAX as usize {
+        bail!("{}x{} is too large for a GIF", width, height);
+    }
+    let mut encoder = Encoder::new(out, width as u16, height as u16, &PALETTE)?;
+    encoder.set_repeat(Repeat::Infinite)?;
+    for frame in frames {
+        let pixels = frame.indexed_pixels(scale);
+        let mut frame = gif::Frame::from_indexed_pixels(width as u16, height as u16, &pixels, None);
+        frame.delay = delay;
+        encoder.write_frame(&frame)?;
+    }
+    Ok(())
+}
+
+pub fn export(frames: &[Frame], dir: &Path, format: Export) -> Result<()> {
+    create_dir_all(dir)?;
+    match format {
+        Export::Ascii => {
+            for frame in frames {
+                let path = dir.join(format!("frame_{:04}.txt", frame.stats.generation));
+                write!(BufWriter::new(File::create(path)?), "{}", frame)?;
+            }
+        }
+        Export::Ppm { scale } => {
+            for frame in frames {
+                let path = dir.join(format!("frame_{:04}.ppm", frame.stats.generation));
+                frame.write_ppm(BufWriter::new(File::create(path)?), scale)?;
+            }
+        }
+        Export::Gif { scale, delay } => {
+            let file = BufWriter::new(File::create(dir.join("room.gif"))?);
+            write_gif(frames, file, scale, delay)?;
+        }
+    }
+    let mut stats = BufWriter::new(File::create(dir.join("stats.csv"))?);
+    writeln!(stats, "generation,occupied,empty,changed")?;
+    for Frame { stats: s, .. } in frames {
+        writeln!(
+            stats,
+            "{},{},{},{}",
+            s.generation, s.occupied, s.empty, s.changed
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        day11::{tests::SAMPLE, Day11Unsafe},
+        grid::Grid2D,
+        Runner,
+    };
+
+    #[test]
+    fn sample_frames() -> Result<()> {
+        let room = Day11Unsafe::get_input(SAMPLE)?;
+        let frames = frames(&room, &Rules::adjacent());
+        assert_eq!(6, frames.len());
+        assert_eq!(
+            Stats {
+                generation: 1,
+                occupied: 71,
+                empty: 0,
+                changed: 71
+            },
+            frames[1].stats
+        );
+        assert_eq!(37, frames.last().unwrap().stats.occupied);
+        let ascii = frames[1].to_string();
+        assert!(ascii.starts_with("generation 1: 71 occupied, 0 empty, 71 changed\n#.##.##.##\n"));
+
+        let mut ppm = Vec::new();
+        frames[0].write_ppm(&mut ppm, 2)?;
+        let header = "P6\n# generation 0: 0 occupied, 71 empty, 0 changed\n20 20\n255\n";
+        assert!(ppm.starts_with(header.as_bytes()));
+        assert_eq!(header.len() + 20 * 20 * 3, ppm.len());
+
+        let mut gif = Vec::new();
+        write_gif(&frames, &mut gif, 3, 20)?;
+        assert!(gif.starts_with(b"GIF89a"));
+        assert_eq!(b';', *gif.last().unwrap());
+        assert!(write_gif(&frames, &mut Vec::new(), 0, 20).is_err());
+        assert!(frames[0].write_ppm(&mut Vec::new(), 0).is_err());
+
+        let empty = super::frames(&Room::new(Grid2D::new(0, 0, vec![])?), &Rules::adjacent());
+        assert_eq!(
+            "generation 0: 0 occupied, 0 empty, 0 changed\n",
+            empty[0].to_string()
+        );
+        assert!(write_gif(&empty, &mut Vec::new(), 1, 20).is_err());
+        Ok(())
+    }
+}
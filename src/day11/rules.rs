@@ -0,0 +1,168 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use anyhow::{bail, Context, Error, Result};
+use itertools::{iproduct, Itertools};
+
+use super::Tile;
+use crate::grid::Boundary;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    pub birth: usize,
+    pub survival: usize,
+    pub range: Option<usize>,
+    pub blocks: Vec<Tile>,
+    pub boundary: Boundary,
+}
+
+impl Rules {
+    pub fn adjacent() -> Self {
+        Self {
+            birth: 0,
+            survival: 3,
+            range: Some(1),
+            blocks: vec![Tile::Floor, Tile::Empty, Tile::Full],
+            boundary: Boundary::Clip,
+        }
+    }
+
+    pub fn line_of_sight() -> Self {
+        Self {
+            birth: 0,
+            survival: 4,
+            range: None,
+            blocks: vec![Tile::Empty, Tile::Full],
+            boundary: Boundary::Clip,
+        }
+    }
+
+    // Seats flip between `L` and `#`, so sight lines only stay fixed when both or neither block
+    pub fn dynamic_sight(&self) -> bool {
+        self.blocks.contains(&Tile::Empty) != self.blocks.contains(&Tile::Full)
+    }
+
+    pub fn apply(&self, tile: Tile, count: usize) -> Tile {
+        match tile {
+            Tile::Empty if count <= self.birth => Tile::Full,
+            Tile::Full if count > self.survival => Tile::Empty,
+            tile => tile,
+        }
+    }
+
+    pub fn neighbors<F>(&self, (x, y): (isize, isize), limit: usize, get: F) -> Vec<(isize, isize)>
+    where
+        F: Fn((isize, isize)) -> Option<Tile>,
+    {
+        let range = self.range.unwrap_or(usize::MAX).min(limit);
+        iproduct!((-1isize..=1), (-1isize..=1))
+            .filter(|&(a, b)| a != 0 || b != 0)
+            .filter_map(|(a, b)| {
+                (1..)
+                    .take(range)
+                    .map(|i| (x + (a * i), y + (b * i)))
+                    .map_while(|coord| get(coord).map(|tile| (coord, tile)))
+                    .find(|(_, tile)| self.blocks.contains(tile))
+                    .map(|(coord, _)| coord)
+            })
+            .collect()
+    }
+}
+
+fn tile(c: char) -> Result<Tile> {
+    Tile::from_char(c).with_context(|| format!("unknown tile `{}`", c))
+}
+
+impl FromStr for Rules {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parts = s
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .collect::<Vec<_>>();
+        let presets = parts
+            .iter()
+            .filter(|&&p| p == "adjacent" || p == "line_of_sight")
+            .collect::<Vec<_>>();
+        let mut rules = match presets[..] {
+            [] | [&"adjacent"] => Self::adjacent(),
+            [&"line_of_sight"] => Self::line_of_sight(),
+            _ => bail!(
+                "expected at most one preset, found {}",
+                presets.iter().join(", ")
+            ),
+        };
+        for part in parts {
+            let (key, value) = match part.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None if part == "adjacent" || part == "line_of_sight" => continue,
+                None => bail!("expected `key=value`, found `{}`", part),
+            };
+            let number = || -> Result<usize> {
+                value
+                    .parse()
+                    .with_context(|| format!("invalid value for `{}`", key))
+            };
+            match key {
+                "birth" => rules.birth = number()?,
+                "survival" => rules.survival = number()?,
+                "range" if value == "inf" => rules.range = None,
+                "range" => rules.range = Some(number()?),
+                "blocks" => rules.blocks = value.chars().map(tile).collect::<Result<_>>()?,
+                "boundary" => rules.boundary = value.parse()?,
+                _ => bail!("unknown rule `{}`", key),
+            }
+        }
+        Ok(rules)
+    }
+}
+
+impl Display for Rules {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let range = self
+            .range
+            .map_or_else(|| "inf".to_owned(), |r| r.to_string());
+        write!(
+            f,
+            "birth={},survival={},range={},blocks={}",
+            self.birth,
+            self.survival,
+            range,
+            self.blocks.iter().join("")
+        )?;
+        if self.boundary != Boundary::Clip {
+            write!(f, ",boundary={}", self.boundary)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() -> Result<()> {
+        assert_eq!(Rules::adjacent(), "".parse()?);
+        assert_eq!(Rules::line_of_sight(), "line_of_sight".parse()?);
+        let rules: Rules = "birth=1, survival=5, range=3, blocks=L".parse()?;
+        assert_eq!("birth=1,survival=5,range=3,blocks=L", rules.to_string());
+        assert_eq!(rules, rules.to_string().parse()?);
+        assert!("range=far".parse::<Rules>().is_err());
+        assert!("blocks=X".parse::<Rules>().is_err());
+
+        let rules: Rules = "survival=3,line_of_sight".parse()?;
+        assert_eq!((None, 3), (rules.range, rules.survival));
+        assert!("adjacent,line_of_sight".parse::<Rules>().is_err());
+
+        let rules: Rules = "boundary=wrap".parse()?;
+        assert_eq!(Boundary::Wrap, rules.boundary);
+        assert_eq!(rules, rules.to_string().parse()?);
+        assert!("boundary=mobius".parse::<Rules>().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use super::{AddressSet, Mask};
+
+pub trait Decoder {
+    fn value(&self, mask: &Mask, val: u64) -> u64;
+    fn addresses(&self, mask: &Mask, addr: u64) -> AddressSet;
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Version1;
+
+impl Decoder for Version1 {
+    fn value(&self, mask: &Mask, val: u64) -> u64 {
+        mask.val(val)
+    }
+
+    fn addresses(&self, _: &Mask, addr: u64) -> AddressSet {
+        AddressSet::single(addr)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Version2;
+
+impl Decoder for Version2 {
+    fn value(&self, _: &Mask, val: u64) -> u64 {
+        val
+    }
+
+    fn addresses(&self, mask: &Mask, addr: u64) -> AddressSet {
+        mask.address_set(addr)
+    }
+}
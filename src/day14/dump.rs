@@ -0,0 +1,215 @@
+use std::fmt::Write;
+
+use anyhow::{bail, Result};
+use itertools::Itertools;
+
+use super::{AddressSet, FloatingMemory};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Hex,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Change {
+    pub addrs: AddressSet,
+    pub before: Option<u64>,
+    pub after: Option<u64>,
+}
+
+fn minus(set: AddressSet, others: &[(AddressSet, u64)]) -> Vec<AddressSet> {
+    others.iter().fold(vec![set], |rest, (other, _)| {
+        rest.iter().flat_map(|r| r.subtract(other)).collect()
+    })
+}
+
+impl FloatingMemory {
+    pub fn dump(&self) -> Vec<(AddressSet, u64)> {
+        self.entries()
+    }
+
+    pub fn expand(&self, cap: u64) -> Result<Vec<(u64, u64)>> {
+        if self.len() > cap as u128 {
+            bail!("{} addresses exceed the cap of {}", self.len(), cap);
+        }
+        Ok(self
+            .entries()
+            .into_iter()
+            .flat_map(|(set, val)| set.addresses().map(move |addr| (addr, val)))
+            .sorted()
+            .collect())
+    }
+
+    pub fn diff(&self, other: &Self) -> Vec<Change> {
+        let (before, after) = (self.entries(), other.entries());
+        let mut changes = Vec::new();
+        for &(a, va) in &before {
+            for &(b, vb) in &after {
+                match a.intersect(&b) {
+                    Some(addrs) if va != vb => changes.push(Change {
+                        addrs,
+                        before: Some(va),
+                        after: Some(vb),
+                    }),
+                    _ => (),
+                }
+            }
+            changes.extend(minus(a, &after).into_iter().map(|addrs| Change {
+                addrs,
+                before: Some(va),
+                after: None,
+            }));
+        }
+        for &(b, vb) in &after {
+            changes.extend(minus(b, &before).into_iter().map(|addrs| Change {
+                addrs,
+                before: None,
+                after: Some(vb),
+            }));
+        }
+        changes.sort_by_key(|c| (c.addrs.value(), c.addrs.floating()));
+        changes
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        let entries = self.entries();
+        let digits = hex_digits(entries.iter().map(|(set, _)| set.value() | set.floating()));
+        let mut out = String::new();
+        match format {
+            Format::Hex => {
+                writeln!(out, "{}", hex_header(digits, "value")).unwrap();
+                for (set, val) in entries {
+                    writeln!(out, "{}  {:#x}", hex_columns(&set, digits), val).unwrap();
+                }
+            }
+            Format::Json => {
+                let lines = entries.iter().map(|(set, val)| {
+                    format!(
+                        "  {{\"address\": {}, \"floating\": {}, \"value\": {}}}",
+                        set.value(),
+                        set.floating(),
+                        val
+                    )
+                });
+                writeln!(out, "[\n{}\n]", lines.format(",\n")).unwrap();
+            }
+        }
+        out
+    }
+}
+
+fn hex_digits(values: impl Iterator<Item = u64>) -> usize {
+    values.map(|v| format!("{:x}", v).len()).max().unwrap_or(1)
+}
+
+fn hex_columns(set: &AddressSet, digits: usize) -> String {
+    let w = digits + 2;
+    format!(
+        "{:<c$}  {:<c$}",
+        format!("{:#0w$x}", set.value(), w = w),
+        format!("{:#0w$x}", set.floating(), w = w),
+        c = w.max("floating".len())
+    )
+}
+
+fn hex_header(digits: usize, rest: &str) -> String {
+    let c = (digits + 2).max("floating".len());
+    format!("{:<c$}  {:<c$}  {}", "address", "floating", rest, c = c)
+}
+
+pub fn render_diff(changes: &[Change], format: Format) -> String {
+    let digits = hex_digits(changes.iter().map(|c| c.addrs.value() | c.addrs.floating()));
+    let mut out = String::new();
+    match format {
+        Format::Hex => {
+            let hex = |v: Option<u64>| v.map_or_else(|| "-".to_owned(), |v| format!("{:#x}", v));
+            writeln!(out, "{}", hex_header(digits, "before -> after")).unwrap();
+            for change in changes {
+                writeln!(
+                    out,
+                    "{}  {} -> {}",
+                    hex_columns(&change.addrs, digits),
+                    hex(change.before),
+                    hex(change.after)
+                )
+                .unwrap();
+            }
+        }
+        Format::Json => {
+            let json = |v: Option<u64>| v.map_or_else(|| "null".to_owned(), |v| v.to_string());
+            let lines = changes.iter().map(|c| {
+                format!(
+                    "  {{\"address\": {}, \"floating\": {}, \"before\": {}, \"after\": {}}}",
+                    c.addrs.value(),
+                    c.addrs.floating(),
+                    json(c.before),
+                    json(c.after)
+                )
+            });
+            writeln!(out, "[\n{}\n]", lines.format(",\n")).unwrap();
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day14::{parse_program, Machine, Version1, Version2, WIDTH};
+
+    #[test]
+    fn decoders() -> anyhow::Result<()> {
+        let program = parse_program(
+            "mask = 000000000000000000000000000000X1001X\n\
+             mem[42] = 100\n\
+             mask = 00000000000000000000000000000000X0XX\n\
+             mem[26] = 1",
+            Some(WIDTH),
+        )?;
+        let mut v1 = Machine::new(Version1);
+        v1.run(&program);
+        let mut v2 = Machine::new(Version2);
+        let snapshots = v2.snapshots(&program);
+        assert_eq!(4, snapshots.len());
+        assert_eq!(
+            vec![(26, 100), (27, 100), (58, 100), (59, 100)],
+            snapshots[1].expand(4)?
+        );
+        assert_eq!(vec![(AddressSet::new(33, 26), 100)], snapshots[1].dump());
+        assert!(snapshots[1].expand(3).is_err());
+
+        assert_eq!(vec![(26, 1), (42, 50)], v1.memory().expand(16)?);
+        let addrs = v2.memory().expand(16)?;
+        assert_eq!(10, addrs.len());
+        assert_eq!((16, 1), addrs[0]);
+        assert_eq!((59, 100), addrs[9]);
+
+        let diff = v1.memory().diff(v2.memory());
+        let removed = diff
+            .iter()
+            .filter(|c| c.after.is_none())
+            .collect::<Vec<_>>();
+        assert_eq!(1, removed.len());
+        assert_eq!(AddressSet::single(42), removed[0].addrs);
+        assert_eq!(
+            9,
+            diff.iter()
+                .filter(|c| c.before.is_none())
+                .map(|c| c.addrs.len())
+                .sum::<u128>()
+        );
+
+        assert_eq!(
+            "address   floating  value\n\
+             0x1a      0x00      0x1\n\
+             0x2a      0x00      0x32\n",
+            v1.memory().render(Format::Hex)
+        );
+        assert_eq!(
+            "[\n  {\"address\": 26, \"floating\": 33, \"before\": null, \"after\": 100}\n]\n",
+            render_diff(&snapshots[0].diff(&snapshots[1]), Format::Json)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AddressSet {
+    floating: u64,
+    value: u64,
+}
+
+impl AddressSet {
+    pub fn new(floating: u64, value: u64) -> Self {
+        Self {
+            floating,
+            value: value & !floating,
+        }
+    }
+
+    pub fn single(addr: u64) -> Self {
+        Self::new(0, addr)
+    }
+
+    pub fn floating(&self) -> u64 {
+        self.floating
+    }
+
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    pub fn len(&self) -> u128 {
+        1 << self.floating.count_ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    pub fn addresses(&self) -> impl Iterator<Item = u64> {
+        let (floating, value) = (self.floating, self.value);
+        let mut sub = Some(0u64);
+        std::iter::from_fn(move || {
+            let curr = sub?;
+            let next = curr.wrapping_sub(floating) & floating;
+            sub = if next == 0 { None } else { Some(next) };
+            Some(value | curr)
+        })
+    }
+
+    pub fn contains(&self, addr: u64) -> bool {
+        addr & !self.floating == self.value
+    }
+
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let fixed = !self.floating & !other.floating;
+        if (self.value ^ other.value) & fixed != 0 {
+            return None;
+        }
+        Some(Self {
+            floating: self.floating & other.floating,
+            value: self.value | other.value,
+        })
+    }
+
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        let inter = match self.intersect(other) {
+            Some(inter) => inter,
+            None => return vec![*self],
+        };
+        // Pin each bit that `other` fixes, one at a time. Every piece disagrees with `inter`
+        // on exactly the bit just pinned, so the pieces are disjoint and miss `other`.
+        let mut split = self.floating & !inter.floating;
+        let mut rest = *self;
+        let mut pieces = Vec::with_capacity(split.count_ones() as usize);
+        while split != 0 {
+            let bit = split & split.wrapping_neg();
+            split &= !bit;
+            rest.floating &= !bit;
+            pieces.push(Self {
+                floating: rest.floating,
+                value: rest.value | (!inter.value & bit),
+            });
+            rest.value |= inter.value & bit;
+        }
+        pieces
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FloatingMemory {
+    exact: BTreeMap<u64, u64>,
+    writes: Vec<(AddressSet, u64)>,
+}
+
+impl FloatingMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write(&mut self, set: AddressSet, val: u64) {
+        if set.floating == 0 {
+            if self.writes.iter().any(|(old, _)| old.contains(set.value)) {
+                self.carve(&set);
+            }
+            self.exact.insert(set.value, val);
+            return;
+        }
+        self.exact.retain(|&addr, _| !set.contains(addr));
+        self.carve(&set);
+        self.writes.push((set, val));
+    }
+
+    fn carve(&mut self, set: &AddressSet) {
+        let mut kept = Vec::with_capacity(self.writes.len());
+        for (old, v) in self.writes.drain(..) {
+            if old.intersect(set).is_some() {
+                kept.extend(old.subtract(set).into_iter().map(|s| (s, v)));
+            } else {
+                kept.push((old, v));
+            }
+        }
+        self.writes = kept;
+    }
+
+    pub fn entries(&self) -> Vec<(AddressSet, u64)> {
+        let mut entries = self.writes.clone();
+        entries.extend(
+            self.exact
+                .iter()
+                .map(|(&addr, &v)| (AddressSet::single(addr), v)),
+        );
+        entries.sort_by_key(|(set, _)| (set.value, set.floating));
+        entries
+    }
+
+    pub fn get(&self, addr: u64) -> Option<u64> {
+        self.exact.get(&addr).copied().or_else(|| {
+            self.writes
+                .iter()
+                .find(|(set, _)| set.contains(addr))
+                .map(|(_, v)| *v)
+        })
+    }
+
+    pub fn len(&self) -> u128 {
+        self.exact.len() as u128 + self.writes.iter().map(|(set, _)| set.len()).sum::<u128>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.exact.is_empty() && self.writes.is_empty()
+    }
+
+    pub fn sum(&self) -> u128 {
+        let exact = self.exact.values().map(|&v| v as u128).sum::<u128>();
+        exact
+            + self
+                .writes
+                .iter()
+                .map(|(set, v)| set.len() * *v as u128)
+                .sum::<u128>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subtract() {
+        let a = AddressSet::new(0b1011, 0b0100);
+        let b = AddressSet::new(0b0110, 0b1000);
+        let pieces = a.subtract(&b);
+        let inter = a.intersect(&b).unwrap();
+        assert_eq!(AddressSet::new(0b0010, 0b1100), inter);
+        assert_eq!(
+            a.len(),
+            pieces.iter().map(AddressSet::len).sum::<u128>() + inter.len()
+        );
+        (0..16).for_each(|addr| {
+            let in_pieces = pieces.iter().filter(|p| p.contains(addr)).count();
+            let expected = a.contains(addr) && !b.contains(addr);
+            assert_eq!(expected as usize, in_pieces, "{:04b}", addr);
+        });
+
+        assert_eq!(vec![a], a.subtract(&AddressSet::single(0b0000)));
+        assert!(a.subtract(&AddressSet::new(0b1111, 0)).is_empty());
+    }
+
+    #[test]
+    fn wide() {
+        let all = (1 << 36) - 1;
+        let mut mem = FloatingMemory::new();
+        mem.write(AddressSet::new(all, 0), 3);
+        mem.write(AddressSet::new(all >> 1, 0), 1);
+        mem.write(AddressSet::single(1 << 35), 7);
+        assert_eq!(1 << 36, mem.len());
+        assert_eq!((1 << 35) * 3 + (1 << 35) + 7 - 3, mem.sum());
+        assert_eq!(Some(1), mem.get(12345));
+        assert_eq!(Some(7), mem.get(1 << 35));
+        assert_eq!(Some(3), mem.get((1 << 35) + 1));
+        assert_eq!(None, mem.get(1 << 36));
+
+        mem.write(AddressSet::new(0b11, 0), 5);
+        assert_eq!(Some(5), mem.get(0));
+        mem.write(AddressSet::single(1), 9);
+        assert_eq!(
+            vec![5, 9, 5, 5],
+            (0..4)
+                .map(|addr| mem.get(addr).unwrap())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(1 << 36, mem.len());
+    }
+}
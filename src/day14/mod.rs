@@ -1,17 +1,33 @@
-use std::{collections::BTreeMap, convert::TryInto};
+use std::convert::TryInto;
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use nom::{
     branch::alt,
-    bytes::complete::tag,
-    character::complete::{char, digit1, multispace0, space0},
-    multi::{many1, many_m_n},
-    sequence::{delimited, terminated, tuple},
+    bytes::complete::{tag, take_till},
+    character::complete::{digit1, space0},
+    combinator::all_consuming,
+    sequence::{delimited, preceded, terminated, tuple},
     IResult,
 };
 
 use crate::Runner;
 
+mod decoder;
+mod dump;
+mod floating;
+
+pub use self::decoder::{Decoder, Version1, Version2};
+pub use self::dump::{render_diff, Change, Format};
+pub use self::floating::{AddressSet, FloatingMemory};
+
+pub const WIDTH: usize = 36;
+pub const MAX_WIDTH: usize = 64;
+const MAX_EXPANSION: u32 = 32;
+
+fn ones(width: usize) -> u64 {
+    u64::MAX >> (MAX_WIDTH - width)
+}
+
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub enum MaskBit {
     Zero,
@@ -19,15 +35,6 @@ pub enum MaskBit {
     X,
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct Mask([MaskBit; 36]);
-
-impl Default for Mask {
-    fn default() -> Self {
-        Self([MaskBit::X; 36])
-    }
-}
-
 impl MaskBit {
     pub fn from_char(c: char) -> Self {
         match c {
@@ -39,57 +46,130 @@ impl MaskBit {
     }
 }
 
+impl std::fmt::Display for MaskBit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Zero => f.write_str("0"),
+            Self::One => f.write_str("1"),
+            Self::X => f.write_str("X"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Mask {
+    width: usize,
+    and: u64,
+    or: u64,
+    floating: u64,
+}
+
+impl Default for Mask {
+    fn default() -> Self {
+        Self::new(WIDTH)
+    }
+}
+
 impl Mask {
-    pub fn val(&self, mut v: u64) -> u64 {
-        (0..36).for_each(|i| match self.0[35 - i] {
-            MaskBit::Zero => v = v & !(1 << i),
-            MaskBit::One => v = v | (1 << i),
-            MaskBit::X => (),
-        });
-        v
-    }
-
-    pub fn val2(&self, v: u64) -> Vec<u64> {
-        let num = self.0.iter().filter(|&&b| b == MaskBit::X).count();
-        let num = num;
-        let mut copies = vec![v; 1 << num as usize];
-        let mut seen = 0;
-        (0..36).for_each(|i| match self.0[35 - i] {
-            MaskBit::Zero => (),
-            MaskBit::One => {
-                copies.iter_mut().for_each(|v| *v = *v | (1 << i));
-            }
-            MaskBit::X => {
-                copies.iter_mut().zip(pattern(1 << num, seen)).for_each(
-                    |(v, bit): (&mut u64, bool)| {
-                        if bit {
-                            *v = *v | 1 << i;
-                        } else {
-                            *v = *v & !(1 << i);
-                        }
-                    },
-                );
-                seen += 1;
+    pub fn new(width: usize) -> Self {
+        assert!((1..=MAX_WIDTH).contains(&width), "bad mask width {}", width);
+        Self {
+            width,
+            and: ones(width),
+            or: 0,
+            floating: ones(width),
+        }
+    }
+
+    pub fn from_bits(bits: &str) -> Result<Self> {
+        if bits.is_empty() {
+            bail!("mask is empty");
+        }
+        if bits.len() > MAX_WIDTH {
+            bail!(
+                "mask has {} bits, at most {} are supported",
+                bits.len(),
+                MAX_WIDTH
+            );
+        }
+        bits.chars().try_fold(
+            Self {
+                width: bits.len(),
+                and: 0,
+                or: 0,
+                floating: 0,
+            },
+            |mask, c| {
+                let (and, or, floating) = match c {
+                    '0' => (0, 0, 0),
+                    '1' => (1, 1, 0),
+                    'X' => (1, 0, 1),
+                    _ => bail!("invalid mask bit `{}`", c),
+                };
+                Ok(Self {
+                    and: mask.and << 1 | and,
+                    or: mask.or << 1 | or,
+                    floating: mask.floating << 1 | floating,
+                    ..mask
+                })
+            },
+        )
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn bit(&self, i: usize) -> MaskBit {
+        let bit = 1 << i;
+        if self.floating & bit != 0 {
+            MaskBit::X
+        } else if self.or & bit != 0 {
+            MaskBit::One
+        } else {
+            MaskBit::Zero
+        }
+    }
+
+    pub fn bits(&self) -> impl Iterator<Item = MaskBit> + '_ {
+        (0..self.width).rev().map(move |i| self.bit(i))
+    }
+
+    pub fn val(&self, v: u64) -> u64 {
+        v & self.and | self.or
+    }
+
+    pub fn val2(&self, v: u64) -> Result<Vec<u64>> {
+        let floating = self.floating.count_ones();
+        if floating > MAX_EXPANSION {
+            bail!(
+                "{} floating bits expand to more than 2^{} addresses",
+                floating,
+                MAX_EXPANSION
+            );
+        }
+        let base = (v | self.or) & ones(self.width) & !self.floating;
+        let mut copies = Vec::with_capacity(1 << floating);
+        let mut sub = 0u64;
+        loop {
+            copies.push(base | sub);
+            sub = sub.wrapping_sub(self.floating) & self.floating;
+            if sub == 0 {
+                break;
             }
-        });
-        copies
+        }
+        Ok(copies)
+    }
+
+    pub fn address_set(&self, addr: u64) -> AddressSet {
+        AddressSet::new(self.floating, (addr | self.or) & ones(self.width))
     }
 }
 
-fn pattern(num: usize, seen: usize) -> Vec<bool> {
-    let mut bit = false;
-    let mut count = 0;
-    let mut v = Vec::new();
-    for _ in 0..num {
-        if count >= 1 << (seen) {
-            bit = !bit;
-            count = 0;
-        }
-        // println!("{} {} {}", bit, count, seen);
-        v.push(bit);
-        count += 1;
+impl std::fmt::Display for Mask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.bits().try_for_each(|bit| write!(f, "{}", bit))
     }
-    v
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -98,25 +178,29 @@ pub enum Operation {
     Mem(u64, u64),
 }
 
-#[derive(Debug)]
-pub struct Machine {
-    mem: BTreeMap<u64, u64>,
+#[derive(Debug, Default, Clone)]
+pub struct Machine<D> {
+    mem: FloatingMemory,
     mask: Mask,
+    decoder: D,
 }
 
-impl Machine {
-    pub fn new() -> Self {
+impl<D: Decoder> Machine<D> {
+    pub fn new(decoder: D) -> Self {
         Self {
             mask: Mask::default(),
             mem: Default::default(),
+            decoder,
         }
     }
 
     pub fn step(&mut self, op: &Operation) -> &Self {
         match op {
-            Operation::Mask(mask) => self.mask = mask.clone(),
+            Operation::Mask(mask) => self.mask = *mask,
             Operation::Mem(addr, val) => {
-                self.mem.insert(*addr, self.mask.val(*val));
+                let addrs = self.decoder.addresses(&self.mask, *addr);
+                let val = self.decoder.value(&self.mask, *val);
+                self.mem.write(addrs, val);
             }
         }
         self
@@ -129,55 +213,72 @@ impl Machine {
         self
     }
 
-    pub fn step2(&mut self, op: &Operation) -> &Self {
-        match op {
-            Operation::Mask(mask) => self.mask = mask.clone(),
-            Operation::Mem(addr, val) => {
-                self.mask.val2(*addr).into_iter().for_each(|addr| {
-                    self.mem.insert(addr, *val);
-                });
-            }
-        }
-        self
+    pub fn snapshots(&mut self, program: &[Operation]) -> Vec<FloatingMemory> {
+        program.iter().map(|op| self.step(op).mem.clone()).collect()
     }
 
-    pub fn run2(&mut self, program: &[Operation]) -> &Self {
-        for op in program {
-            self.step2(op);
-        }
-        self
+    pub fn memory(&self) -> &FloatingMemory {
+        &self.mem
+    }
+
+    pub fn sum(&self) -> u128 {
+        self.mem.sum()
     }
 }
 
-fn mask(input: &str) -> IResult<&str, Operation> {
-    let (input, mask): (&str, Vec<char>) = delimited(
-        tuple((tag("mask"), multispace0, tag("="), multispace0)),
-        many_m_n(36, 36, alt((char('0'), char('1'), char('X')))),
-        multispace0,
-    )(input)?;
+enum Line<'a> {
+    Mask(&'a str),
+    Mem(u64, u64),
+}
 
-    Ok((
-        input,
-        Operation::Mask(Mask(
-            mask.into_iter()
-                .map(MaskBit::from_char)
-                .collect::<Vec<MaskBit>>()
-                .try_into()
-                .unwrap(),
-        )),
-    ))
+fn mask(input: &str) -> IResult<&str, Line<'_>> {
+    let (input, bits) = preceded(
+        tuple((tag("mask"), space0, tag("="), space0)),
+        take_till(|c: char| c.is_whitespace()),
+    )(input)?;
+    Ok((input, Line::Mask(bits)))
 }
 
-fn mem(input: &str) -> IResult<&str, Operation> {
+fn mem(input: &str) -> IResult<&str, Line<'_>> {
     let (input, mem) = delimited(tag("mem["), digit1, tuple((tag("] ="), space0)))(input)?;
     let mem = mem.parse::<u64>().unwrap();
-    let (input, b) = terminated(digit1, multispace0)(input)?;
+    let (input, b) = terminated(digit1, space0)(input)?;
     let b = b.parse::<u64>().unwrap();
-    Ok((input, Operation::Mem(mem, b)))
+    Ok((input, Line::Mem(mem, b)))
 }
 
-fn parse_program(input: &str) -> IResult<&str, Vec<Operation>> {
-    many1(terminated(alt((mem, mask)), multispace0))(input)
+pub fn parse_program(input: &str, width: Option<usize>) -> Result<Vec<Operation>> {
+    let mut masked = false;
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| {
+            let line = match all_consuming(alt((mem, mask)))(line.trim()) {
+                Ok((_, line)) => line,
+                Err(_) => bail!("line {}: cannot parse `{}`", n + 1, line),
+            };
+            Ok(match line {
+                Line::Mem(_, _) if !masked => {
+                    bail!("line {}: `mem` before the first `mask`", n + 1)
+                }
+                Line::Mem(addr, val) => Operation::Mem(addr, val),
+                Line::Mask(bits) => {
+                    masked = true;
+                    let mask = Mask::from_bits(bits).with_context(|| format!("line {}", n + 1))?;
+                    match width {
+                        Some(width) if width != mask.width() => bail!(
+                            "line {}: mask has {} bits, expected {}",
+                            n + 1,
+                            mask.width(),
+                            width
+                        ),
+                        _ => Operation::Mask(mask),
+                    }
+                }
+            })
+        })
+        .collect()
 }
 
 pub struct Day14;
@@ -191,20 +292,19 @@ impl Runner for Day14 {
     }
 
     fn get_input(input: &str) -> Result<Self::Input> {
-        Ok(parse_program(input).unwrap().1)
+        parse_program(input, Some(WIDTH))
     }
 
     fn part1(input: &Self::Input) -> Result<Self::Output> {
-        let mut machine = Machine::new();
+        let mut machine = Machine::new(Version1);
         machine.run(input);
-        Ok(machine.mem.values().sum())
+        Ok(machine.sum().try_into()?)
     }
 
     fn part2(input: &Self::Input) -> Result<Self::Output> {
-        let mut machine = Machine::new();
-        machine.run2(input);
-        // println!("{:#?}", machine.mem);
-        Ok(machine.mem.values().sum())
+        let mut machine = Machine::new(Version2);
+        machine.run(input);
+        Ok(machine.sum().try_into()?)
     }
 }
 
@@ -237,4 +337,95 @@ mod tests {
         assert_eq!(208, Day14::part2(&input)?);
         Ok(())
     }
+
+    #[test]
+    fn all_floating() -> Result<()> {
+        let input = "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX\n\
+                     mem[0] = 2\n\
+                     mask = 1XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX\n\
+                     mem[0] = 1";
+
+        let input = Day14::get_input(input)?;
+        assert_eq!((1 << 35) * 3, Day14::part2(&input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn custom_decoder() -> Result<()> {
+        struct Both;
+        impl Decoder for Both {
+            fn value(&self, mask: &Mask, val: u64) -> u64 {
+                Version1.value(mask, val)
+            }
+            fn addresses(&self, mask: &Mask, addr: u64) -> AddressSet {
+                Version2.addresses(mask, addr)
+            }
+        }
+
+        let input = "mask = 000000000000000000000000000000X1001X\n\
+                     mem[42] = 100";
+        let input = Day14::get_input(input)?;
+        let mut machine = Machine::new(Both);
+        machine.run(&input);
+        assert_eq!(4 * 50, machine.sum());
+        Ok(())
+    }
+
+    #[test]
+    fn widths() -> Result<()> {
+        let input = "mask = X1X0\n\
+                     mem[1] = 15\n\
+                     mask = 1X\n\
+                     mem[3] = 1";
+        let err = parse_program(input, Some(WIDTH)).unwrap_err();
+        assert_eq!("line 1: mask has 4 bits, expected 36", err.to_string());
+
+        let program = parse_program(input, None)?;
+        let mut machine = Machine::new(Version1);
+        machine.run(&program);
+        assert_eq!(0b1110 + 0b11, machine.sum());
+        let mut machine = Machine::new(Version2);
+        machine.run(&program);
+        assert_eq!(4 * 15 + 2, machine.sum());
+
+        let wide = format!("mask = {}\nmem[0] = 0", "1".repeat(64));
+        let program = parse_program(&wide, Some(64))?;
+        let mut machine = Machine::new(Version1);
+        machine.run(&program);
+        assert_eq!(u64::MAX as u128, machine.sum());
+
+        let floating = format!("mask = {}\nmem[0] = 3", "X".repeat(64));
+        let program = parse_program(&floating, Some(64))?;
+        let mut machine = Machine::new(Version2);
+        machine.run(&program);
+        assert_eq!(1 << 64, machine.memory().len());
+        assert_eq!(3 << 64, machine.sum());
+
+        let err = parse_program(&format!("mask = {}", "X".repeat(65)), None).unwrap_err();
+        assert_eq!(
+            "line 1: mask has 65 bits, at most 64 are supported",
+            format!("{:#}", err)
+        );
+        let err = parse_program("mask = 1\nmem[1] = 2\nmask = 10Y1", None).unwrap_err();
+        assert_eq!("line 3: invalid mask bit `Y`", format!("{:#}", err));
+        let err = parse_program("mem[1] = 2\nmask = 1X", None).unwrap_err();
+        assert_eq!("line 1: `mem` before the first `mask`", err.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn mask_bits() -> Result<()> {
+        let text = "000000000000000000000000000000X1001X";
+        let mask = Mask::from_bits(text).unwrap();
+        assert_eq!(text, mask.to_string());
+        assert_eq!(MaskBit::X, mask.bit(0));
+        assert_eq!(MaskBit::One, mask.bit(1));
+        assert_eq!(MaskBit::Zero, mask.bit(35));
+        assert_eq!(vec![26, 27, 58, 59], mask.val2(42)?);
+        assert!(Mask::from_bits(&"X".repeat(64))?.val2(0).is_err());
+        let mask = Mask::from_bits("XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X").unwrap();
+        assert_eq!(73, mask.val(11));
+        assert_eq!(64, mask.val(0));
+        Ok(())
+    }
 }
@@ -1,10 +1,26 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 
-use crate::Runner;
+use crate::{
+    automaton::{conway, Automaton, Coord, Offsets, Sparse},
+    Runner,
+};
 
 pub struct Day17;
+
+fn simulate<const N: usize>(input: &<Day17 as Runner>::Input, cycles: usize) -> usize {
+    let mut cubes = Sparse::new(Offsets::<N>::moore());
+    input.iter().for_each(|&[x, y]| {
+        let mut coord = [0; N];
+        coord[0] = x;
+        coord[1] = y;
+        cubes.insert(coord, true);
+    });
+    cubes.run(&conway(&[2, 3], &[3]), cycles);
+    cubes.count(true)
+}
+
 impl Runner for Day17 {
-    type Input = Vec<()>;
+    type Input = Vec<Coord<2>>;
     type Output = usize;
 
     fn day() -> usize {
@@ -12,15 +28,25 @@ impl Runner for Day17 {
     }
 
     fn get_input(input: &str) -> Result<Self::Input> {
-        todo!()
+        let mut active = Vec::new();
+        for (y, line) in input.lines().enumerate() {
+            for (x, c) in line.trim().chars().enumerate() {
+                match c {
+                    '#' => active.push([x as i32, y as i32]),
+                    '.' => (),
+                    _ => bail!("line {}: unexpected character `{}`", y + 1, c),
+                }
+            }
+        }
+        Ok(active)
     }
 
     fn part1(input: &Self::Input) -> Result<Self::Output> {
-        todo!()
+        Ok(simulate::<3>(input, 6))
     }
 
     fn part2(input: &Self::Input) -> Result<Self::Output> {
-        todo!()
+        Ok(simulate::<4>(input, 6))
     }
 }
 
@@ -30,12 +56,15 @@ mod tests {
 
     #[test]
     fn sample1() -> Result<()> {
-        let input = "0,3,6";
+        let input = ".#.\n\
+                     ..#\n\
+                     ###";
 
         let input = Day17::get_input(input)?;
         println!("{:?}", input);
-        assert_eq!(436, Day17::part1(&input)?);
-        assert_eq!(175594, Day17::part2(&input)?);
+        assert_eq!(5, input.len());
+        assert_eq!(112, Day17::part1(&input)?);
+        assert_eq!(848, Day17::part2(&input)?);
         Ok(())
     }
 }
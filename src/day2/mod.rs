@@ -9,8 +9,26 @@ use nom::{
 
 use crate::Runner;
 
+mod policy;
+
+pub use self::policy::{
+    validate, CharClass, CountInRange, ExactlyOnePosition, Failure, PasswordPolicy, Pattern,
+};
+
 pub struct Day02;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub low: usize,
+    pub high: usize,
+    pub letter: u8,
+    pub password: Vec<u8>,
+}
+
+pub fn count_valid<P: PasswordPolicy>(input: &[Entry], policy: &P) -> usize {
+    input.iter().filter(|entry| policy.is_valid(entry)).count()
+}
+
 fn parse_line(input: &str) -> nom::IResult<&str, (usize, usize, u8, Vec<u8>)> {
     let (input, (s, _dash, e)) = terminated(tuple((digit1, tag("-"), digit1)), space1)(input)?;
     let (input, n) = terminated(anychar, tuple((tag(":"), space1)))(input)?;
@@ -26,9 +44,8 @@ fn parse_line(input: &str) -> nom::IResult<&str, (usize, usize, u8, Vec<u8>)> {
     ))
 }
 
-type Pair = ((usize, usize), u8);
 impl Runner for Day02 {
-    type Input = Vec<(Pair, Vec<u8>)>;
+    type Input = Vec<Entry>;
     type Output = usize;
 
     fn day() -> usize {
@@ -39,9 +56,14 @@ impl Runner for Day02 {
     fn get_input(input: &str) -> Result<Self::Input> {
         let lines = input.lines();
         let lines = lines.map(|line| {
-            let (input, (start, end, c, pass)) = parse_line(line).unwrap();
+            let (input, (low, high, letter, password)) = parse_line(line).unwrap();
             assert_eq!("", input);
-            (((start, end), c), pass)
+            Entry {
+                low,
+                high,
+                letter,
+                password,
+            }
         });
         let lines = lines.collect::<Self::Input>();
         Ok(lines)
@@ -49,18 +71,49 @@ impl Runner for Day02 {
 
     #[inline]
     fn part1(input: &Self::Input) -> Result<usize> {
-        let correct = input.iter().filter(|&(((s, e), c), pass)| -> bool {
-            (*s..=*e).contains(&bytecount::count(pass, *c))
-        });
-        Ok(correct.count())
+        Ok(count_valid(input, &CountInRange))
     }
 
     #[inline]
     fn part2(input: &Self::Input) -> Result<usize> {
-        let correct = input
+        Ok(count_valid(input, &ExactlyOnePosition))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::bytes::Regex;
+
+    #[test]
+    fn sample1() -> Result<()> {
+        let input = Day02::get_input("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc")?;
+        assert_eq!(2, Day02::part1(&input)?);
+        assert_eq!(1, Day02::part2(&input)?);
+
+        let pattern = Pattern(Regex::new("^a")?);
+        let policies: [&dyn PasswordPolicy; 4] = [
+            &CountInRange,
+            &ExactlyOnePosition,
+            &pattern,
+            &CharClass::lowercase(6),
+        ];
+        let report = validate(&input, &policies)
             .iter()
-            .map(|(((f, s), c), ref pass)| (pass[*f - 1] == *c as u8, pass[*s - 1] == *c as u8))
-            .filter(|(a, b)| a != b);
-        Ok(correct.count())
+            .map(Failure::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "line 1: lowercase >= 6: has 5 lowercase characters, expected at least 6",
+                "line 2: count: `b` appears 0 times, expected 1 to 3",
+                "line 2: position: `b` is at neither position 1 nor 3",
+                "line 2: pattern /^a/: password `cdefg` does not match",
+                "line 2: lowercase >= 6: has 5 lowercase characters, expected at least 6",
+                "line 3: position: `c` is at both positions 2 and 9",
+                "line 3: pattern /^a/: password `ccccccccc` does not match",
+            ],
+            report
+        );
+        Ok(())
     }
 }
@@ -0,0 +1,173 @@
+use std::fmt::{self, Display, Formatter};
+
+use regex::bytes::Regex;
+
+use super::Entry;
+
+pub trait PasswordPolicy {
+    fn name(&self) -> String;
+    fn is_valid(&self, entry: &Entry) -> bool;
+    fn explain(&self, entry: &Entry) -> String;
+}
+
+pub struct CountInRange;
+
+impl CountInRange {
+    fn count(entry: &Entry) -> usize {
+        bytecount::count(&entry.password, entry.letter)
+    }
+}
+
+impl PasswordPolicy for CountInRange {
+    fn name(&self) -> String {
+        "count".to_owned()
+    }
+
+    fn is_valid(&self, entry: &Entry) -> bool {
+        (entry.low..=entry.high).contains(&Self::count(entry))
+    }
+
+    fn explain(&self, entry: &Entry) -> String {
+        format!(
+            "`{}` appears {} times, expected {} to {}",
+            entry.letter as char,
+            Self::count(entry),
+            entry.low,
+            entry.high
+        )
+    }
+}
+
+pub struct ExactlyOnePosition;
+
+impl ExactlyOnePosition {
+    fn matches(entry: &Entry, position: usize) -> bool {
+        position > 0 && entry.password.get(position - 1) == Some(&entry.letter)
+    }
+}
+
+impl PasswordPolicy for ExactlyOnePosition {
+    fn name(&self) -> String {
+        "position".to_owned()
+    }
+
+    fn is_valid(&self, entry: &Entry) -> bool {
+        Self::matches(entry, entry.low) != Self::matches(entry, entry.high)
+    }
+
+    fn explain(&self, entry: &Entry) -> String {
+        let (which, joiner) = if Self::matches(entry, entry.low) {
+            ("both positions", "and")
+        } else {
+            ("neither position", "nor")
+        };
+        format!(
+            "`{}` is at {} {} {} {}",
+            entry.letter as char, which, entry.low, joiner, entry.high
+        )
+    }
+}
+
+pub struct Pattern(pub Regex);
+
+impl PasswordPolicy for Pattern {
+    fn name(&self) -> String {
+        format!("pattern /{}/", self.0)
+    }
+
+    fn is_valid(&self, entry: &Entry) -> bool {
+        self.0.is_match(&entry.password)
+    }
+
+    fn explain(&self, entry: &Entry) -> String {
+        format!(
+            "password `{}` does not match",
+            String::from_utf8_lossy(&entry.password)
+        )
+    }
+}
+
+pub struct CharClass {
+    pub name: &'static str,
+    pub class: fn(&u8) -> bool,
+    pub min: usize,
+}
+
+impl CharClass {
+    pub fn lowercase(min: usize) -> Self {
+        Self {
+            name: "lowercase",
+            class: u8::is_ascii_lowercase,
+            min,
+        }
+    }
+
+    pub fn uppercase(min: usize) -> Self {
+        Self {
+            name: "uppercase",
+            class: u8::is_ascii_uppercase,
+            min,
+        }
+    }
+
+    pub fn digits(min: usize) -> Self {
+        Self {
+            name: "digit",
+            class: u8::is_ascii_digit,
+            min,
+        }
+    }
+
+    fn count(&self, entry: &Entry) -> usize {
+        entry.password.iter().filter(|c| (self.class)(c)).count()
+    }
+}
+
+impl PasswordPolicy for CharClass {
+    fn name(&self) -> String {
+        format!("{} >= {}", self.name, self.min)
+    }
+
+    fn is_valid(&self, entry: &Entry) -> bool {
+        self.count(entry) >= self.min
+    }
+
+    fn explain(&self, entry: &Entry) -> String {
+        format!(
+            "has {} {} characters, expected at least {}",
+            self.count(entry),
+            self.name,
+            self.min
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub line: usize,
+    pub policy: String,
+    pub reason: String,
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}: {}", self.line, self.policy, self.reason)
+    }
+}
+
+pub fn validate(entries: &[Entry], policies: &[&dyn PasswordPolicy]) -> Vec<Failure> {
+    entries
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, entry)| {
+            policies
+                .iter()
+                .filter(move |policy| !policy.is_valid(entry))
+                .map(move |policy| Failure {
+                    line: idx + 1,
+                    policy: policy.name(),
+                    reason: policy.explain(entry),
+                })
+        })
+        .collect()
+}
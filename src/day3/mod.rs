@@ -1,24 +1,88 @@
-use anyhow::Result;
-use ndarray::Array2;
+use std::ops::RangeInclusive;
 
-use crate::Runner;
+use anyhow::{bail, Result};
+use itertools::iproduct;
+
+use crate::{
+    grid::{Boundary, Grid2D},
+    Runner,
+};
+
+pub type Slope = (usize, usize);
+
+pub const SLOPES: [Slope; 5] = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
 
 pub struct Day03;
 
 impl Day03 {
     fn get_trees(input: &<Self as Runner>::Input, run: usize, rise: usize) -> Result<usize> {
-        Ok((0..input.dim().0)
-            // .take_while(|&i| i < input.dim().1)
-            .map(|i| ((i * run) % input.dim().1, i * rise))
-            .into_iter()
-            .filter(|&(_, x)| x < input.dim().0)
-            .filter(|&(y, x)| input[(x, y)])
+        Ok(path(input, (run, rise))?
+            .filter(|&coord| input[coord])
             .count())
     }
 }
 
+fn path(
+    input: &Grid2D<bool>,
+    (run, rise): Slope,
+) -> Result<impl Iterator<Item = (usize, usize)> + '_> {
+    if rise == 0 {
+        bail!("slope {}/{} never reaches the bottom", run, rise);
+    }
+    Ok((0..input.height().div_ceil(rise))
+        .map(move |i| input.resolve((i as isize * run as isize, (i * rise) as isize)))
+        .map_while(|coord| coord))
+}
+
+pub fn trees(input: &Grid2D<bool>, slopes: &[Slope]) -> Result<Vec<usize>> {
+    slopes
+        .iter()
+        .map(|&(run, rise)| Day03::get_trees(input, run, rise))
+        .collect()
+}
+
+pub fn least_trees(
+    input: &Grid2D<bool>,
+    runs: RangeInclusive<usize>,
+    rises: RangeInclusive<usize>,
+) -> Result<Option<(Slope, usize)>> {
+    let mut best: Option<(Slope, usize)> = None;
+    for slope in iproduct!(runs, rises) {
+        let count = Day03::get_trees(input, slope.0, slope.1)?;
+        if best.is_none_or(|(_, least)| count < least) {
+            best = Some((slope, count));
+        }
+    }
+    Ok(best)
+}
+
+pub fn render_path(input: &Grid2D<bool>, (run, rise): Slope) -> Result<String> {
+    let steps = path(input, (run, rise))?.count();
+    let width = input.width();
+    let repeats = ((steps.saturating_sub(1) * run) / width.max(1)) + 1;
+    let mut rows = input
+        .rows()
+        .map(|row| {
+            row.iter()
+                .map(|&tree| if tree { '#' } else { '.' })
+                .cycle()
+                .take(width * repeats)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    for i in 0..steps {
+        let cell = &mut rows[i * rise][i * run];
+        *cell = if *cell == '#' { 'X' } else { 'O' };
+    }
+    Ok(rows
+        .into_iter()
+        .map(|row| row.into_iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
 impl Runner for Day03 {
-    type Input = Array2<bool>;
+    type Input = Grid2D<bool>;
     type Output = usize;
 
     fn day() -> usize {
@@ -26,16 +90,13 @@ impl Runner for Day03 {
     }
 
     fn get_input(input: &str) -> Result<Self::Input> {
-        let height = input.lines().count();
-        let width = input.lines().next().unwrap().len();
-        let vec = input
-            .lines()
-            .map(|l| l.chars().map(|c| c == '#'))
-            .flatten()
-            .collect();
-        let array = Array2::from_shape_vec((height, width), vec);
-
-        Ok(array?)
+        let grid = Grid2D::parse(input, |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })?;
+
+        Ok(grid.with_boundary(Boundary::WrapX))
     }
 
     fn part1(input: &Self::Input) -> Result<usize> {
@@ -43,40 +104,57 @@ impl Runner for Day03 {
     }
 
     fn part2(input: &Self::Input) -> Result<usize> {
-        Ok([
-            Self::get_trees(input, 1, 1)?,
-            Self::get_trees(input, 3, 1)?,
-            Self::get_trees(input, 5, 1)?,
-            Self::get_trees(input, 7, 1)?,
-            Self::get_trees(input, 1, 2)?,
-        ]
-        .iter()
-        .product())
+        Ok(trees(input, &SLOPES)?.iter().product())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    // use super::*;
-
-    // #[test]
-    // fn sample1() -> Result<()> {
-    //     let input = get_input(
-    //         "..##.......\n\
-    //          #...#...#..\n\
-    //          .#....#..#.\n\
-    //          ..#.#...#.#\n\
-    //          .#...##..#.\n\
-    //          ..#.##.....\n\
-    //          .#.#.#....#\n\
-    //          .#........#\n\
-    //          #.##...#...\n\
-    //          #...##....#\n\
-    //          .#..#...#.#",
-    //     )
-    //     .unwrap();
-    //     assert_eq!(input.dim(), (11, 11));
-    //     assert_eq!(7, part1(&input).unwrap());
-    //     Ok(())
-    // }
+    use super::*;
+
+    const SAMPLE: &str = "..##.......\n\
+                          #...#...#..\n\
+                          .#....#..#.\n\
+                          ..#.#...#.#\n\
+                          .#...##..#.\n\
+                          ..#.##.....\n\
+                          .#.#.#....#\n\
+                          .#........#\n\
+                          #.##...#...\n\
+                          #...##....#\n\
+                          .#..#...#.#";
+
+    #[test]
+    fn sample1() -> Result<()> {
+        let input = Day03::get_input(SAMPLE)?;
+        assert_eq!(input.dim(), (11, 11));
+        assert_eq!(7, Day03::part1(&input)?);
+        assert_eq!(336, Day03::part2(&input)?);
+        assert_eq!(vec![2, 7, 3, 4, 2], trees(&input, &SLOPES)?);
+        assert!(trees(&input, &[(1, 0)]).is_err());
+        for (boundary, expected) in [
+            (Boundary::Clip, vec![2, 1, 0, 0, 2]),
+            (Boundary::Wrap, vec![2, 7, 3, 4, 2]),
+            (Boundary::Reflect, vec![2, 4, 5, 3, 2]),
+        ] {
+            let input = input.clone().with_boundary(boundary);
+            assert_eq!(expected, trees(&input, &SLOPES)?, "{}", boundary);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn slopes() -> Result<()> {
+        let input = Day03::get_input(SAMPLE)?;
+        let map = render_path(&input, (3, 1))?;
+        let rows = map.lines().collect::<Vec<_>>();
+        assert_eq!(11, rows.len());
+        assert_eq!("O.##.......", &rows[0][..11]);
+        assert_eq!("#..O#...#..", &rows[1][..11]);
+        assert_eq!(".#....X..#.", &rows[2][..11]);
+        assert_eq!(33, rows[10].len());
+
+        assert_eq!(Some(((5, 2), 0)), least_trees(&input, 1..=7, 1..=2)?);
+        Ok(())
+    }
 }
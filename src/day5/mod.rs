@@ -1,15 +1,16 @@
 use std::collections::BTreeSet;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 use crate::Runner;
 
-pub struct Day05;
+mod pass;
+mod seats;
 
-#[allow(dead_code)]
-fn row(id: &usize) -> usize {
-    id >> 3
-}
+pub use self::pass::{BoardingPass, Plane};
+pub use self::seats::{seat_map, Analysis};
+
+pub struct Day05;
 
 impl Runner for Day05 {
     type Input = BTreeSet<usize>;
@@ -20,18 +21,11 @@ impl Runner for Day05 {
     }
 
     fn get_input(input: &str) -> Result<Self::Input> {
-        Ok(input
+        let plane = Plane::default();
+        input
             .lines()
-            .map(|l| {
-                l.chars().rev().enumerate().fold(0, |acc, (i, c)| {
-                    if c == 'B' || c == 'R' {
-                        acc + (1 << i)
-                    } else {
-                        acc
-                    }
-                })
-            })
-            .collect())
+            .map(|l| Ok(BoardingPass::decode(l, &plane)?.id(&plane)))
+            .collect()
     }
 
     fn part1(input: &Self::Input) -> Result<usize> {
@@ -39,12 +33,11 @@ impl Runner for Day05 {
     }
 
     fn part2(input: &Self::Input) -> Result<usize> {
-        Ok(input
-            .iter()
-            .zip((*input.iter().min().unwrap())..)
-            .filter_map(|(id, o)| if *id != o { Some(o) } else { None })
-            .next()
-            .unwrap())
+        Analysis::new(input, &Plane::default())
+            .interior()
+            .find(|block| block.start() == block.end())
+            .map(|block| *block.start())
+            .context("no single empty seat between occupied ones")
     }
 }
 
@@ -0,0 +1,171 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use anyhow::{bail, Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Plane {
+    row_bits: u32,
+    column_bits: u32,
+}
+
+impl Default for Plane {
+    fn default() -> Self {
+        Self {
+            row_bits: 7,
+            column_bits: 3,
+        }
+    }
+}
+
+impl Plane {
+    pub fn new(rows: usize, columns: usize) -> Result<Self> {
+        if !rows.is_power_of_two() || !columns.is_power_of_two() {
+            bail!(
+                "a {}x{} plane cannot be partitioned, both sides must be powers of two",
+                rows,
+                columns
+            );
+        }
+        Ok(Self {
+            row_bits: rows.trailing_zeros(),
+            column_bits: columns.trailing_zeros(),
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        1 << self.row_bits
+    }
+
+    pub fn columns(&self) -> usize {
+        1 << self.column_bits
+    }
+
+    pub fn seats(&self) -> usize {
+        self.rows() * self.columns()
+    }
+
+    pub fn code_len(&self) -> usize {
+        (self.row_bits + self.column_bits) as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BoardingPass {
+    pub row: usize,
+    pub column: usize,
+}
+
+fn decode_half(code: &str, offset: usize, (low, high): (char, char)) -> Result<usize> {
+    code.chars().enumerate().try_fold(0, |acc, (i, c)| match c {
+        c if c == low => Ok(acc << 1),
+        c if c == high => Ok((acc << 1) | 1),
+        _ => bail!(
+            "`{}` at position {} is not {} or {}",
+            c,
+            offset + i + 1,
+            low,
+            high
+        ),
+    })
+}
+
+impl BoardingPass {
+    pub fn decode(code: &str, plane: &Plane) -> Result<Self> {
+        if !code.is_ascii() || code.len() != plane.code_len() {
+            bail!(
+                "expected {} characters, found {} in `{}`",
+                plane.code_len(),
+                code.chars().count(),
+                code
+            );
+        }
+        let (rows, columns) = code.split_at(plane.row_bits as usize);
+        Ok(Self {
+            row: decode_half(rows, 0, ('F', 'B'))?,
+            column: decode_half(columns, rows.len(), ('L', 'R'))?,
+        })
+    }
+
+    pub fn encode(&self, plane: &Plane) -> String {
+        let half = |value: usize, bits: u32, (low, high)| {
+            (0..bits)
+                .rev()
+                .map(move |bit| if value >> bit & 1 == 1 { high } else { low })
+        };
+        half(self.row, plane.row_bits, ('F', 'B'))
+            .chain(half(self.column, plane.column_bits, ('L', 'R')))
+            .collect()
+    }
+
+    pub fn from_id(id: usize, plane: &Plane) -> Result<Self> {
+        if id >= plane.seats() {
+            bail!("seat {} is outside a plane of {} seats", id, plane.seats());
+        }
+        Ok(Self {
+            row: id / plane.columns(),
+            column: id % plane.columns(),
+        })
+    }
+
+    pub fn id(&self, plane: &Plane) -> usize {
+        self.row * plane.columns() + self.column
+    }
+}
+
+impl FromStr for BoardingPass {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::decode(s, &Plane::default())
+    }
+}
+
+impl Display for BoardingPass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.encode(&Plane::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codec() -> Result<()> {
+        let plane = Plane::default();
+        for &(code, row, column, id) in &[
+            ("FBFBBFFRLR", 44, 5, 357),
+            ("BFFFBBFRRR", 70, 7, 567),
+            ("FFFBBBFRRR", 14, 7, 119),
+            ("BBFFBBFRLL", 102, 4, 820),
+        ] {
+            let pass: BoardingPass = code.parse()?;
+            assert_eq!(BoardingPass { row, column }, pass);
+            assert_eq!(id, pass.id(&plane));
+            assert_eq!(pass, BoardingPass::from_id(id, &plane)?);
+            assert_eq!(code, pass.to_string());
+        }
+
+        let small = Plane::new(4, 2)?;
+        let pass = BoardingPass::decode("BFR", &small)?;
+        assert_eq!(5, pass.id(&small));
+        assert_eq!("BFR", pass.encode(&small));
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        let err = "FBFBBFFRL".parse::<BoardingPass>().unwrap_err();
+        assert_eq!(
+            "expected 10 characters, found 9 in `FBFBBFFRL`",
+            err.to_string()
+        );
+        let err = "FBFBBFFRLB".parse::<BoardingPass>().unwrap_err();
+        assert_eq!("`B` at position 10 is not L or R", err.to_string());
+        assert!(Plane::new(100, 8).is_err());
+        assert!(BoardingPass::from_id(1024, &Plane::default()).is_err());
+    }
+}
@@ -0,0 +1,105 @@
+use std::{collections::BTreeSet, ops::RangeInclusive};
+
+use super::Plane;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis {
+    pub empty: Vec<usize>,
+    pub blocks: Vec<RangeInclusive<usize>>,
+    pub front: Vec<usize>,
+    pub back: Vec<usize>,
+    pub plane: Plane,
+}
+
+impl Analysis {
+    pub fn new(seats: &BTreeSet<usize>, plane: &Plane) -> Self {
+        let empty = (0..plane.seats())
+            .filter(|id| !seats.contains(id))
+            .collect::<Vec<_>>();
+
+        let mut blocks: Vec<RangeInclusive<usize>> = Vec::new();
+        for &id in &empty {
+            match blocks.last_mut() {
+                Some(block) if *block.end() + 1 == id => *block = *block.start()..=id,
+                _ => blocks.push(id..=id),
+            }
+        }
+
+        let row_empty = |row: &usize| {
+            let start = row * plane.columns();
+            seats.range(start..start + plane.columns()).next().is_none()
+        };
+        let front = (0..plane.rows()).take_while(row_empty).collect::<Vec<_>>();
+        let back = (front.len()..plane.rows())
+            .rev()
+            .take_while(row_empty)
+            .collect::<Vec<_>>();
+
+        Self {
+            empty,
+            blocks,
+            front,
+            back,
+            plane: *plane,
+        }
+    }
+
+    pub fn interior(&self) -> impl Iterator<Item = &RangeInclusive<usize>> {
+        let last = self.plane.seats() - 1;
+        self.blocks
+            .iter()
+            .filter(move |block| *block.start() != 0 && *block.end() != last)
+    }
+}
+
+pub fn seat_map(seats: &BTreeSet<usize>, plane: &Plane) -> String {
+    let width = (plane.rows() - 1).to_string().len().max(3);
+    (0..plane.rows())
+        .map(|row| {
+            let seats = (0..plane.columns())
+                .map(|column| {
+                    if seats.contains(&(row * plane.columns() + column)) {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>();
+            format!("{:>width$} {}", row, seats, width = width)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn gaps() -> Result<()> {
+        let plane = Plane::new(4, 4)?;
+        let seats = (4..12)
+            .filter(|&id| id != 6 && id != 9 && id != 10)
+            .collect();
+        let analysis = Analysis::new(&seats, &plane);
+        assert_eq!(vec![0, 1, 2, 3, 6, 9, 10, 12, 13, 14, 15], analysis.empty);
+        assert_eq!(vec![0..=3, 6..=6, 9..=10, 12..=15], analysis.blocks);
+        assert_eq!(
+            vec![6..=6, 9..=10],
+            analysis.interior().cloned().collect::<Vec<_>>()
+        );
+        assert_eq!(vec![0], analysis.front);
+        assert_eq!(vec![3], analysis.back);
+        assert_eq!(
+            "  0 ....\n  1 ##.#\n  2 #..#\n  3 ....",
+            seat_map(&seats, &plane)
+        );
+
+        let tall = Plane::new(2048, 1)?;
+        let map = seat_map(&BTreeSet::new(), &tall);
+        assert!(map.starts_with("   0 .\n"));
+        assert!(map.ends_with("\n2047 ."));
+        Ok(())
+    }
+}
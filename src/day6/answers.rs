@@ -0,0 +1,173 @@
+use anyhow::{bail, Context, Result};
+
+pub trait Answers: Clone {
+    fn empty() -> Self;
+    fn insert(&mut self, c: char) -> Result<()>;
+    fn union(&self, other: &Self) -> Self;
+    fn intersection(&self, other: &Self) -> Self;
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Answers for u32 {
+    fn empty() -> Self {
+        0
+    }
+
+    fn insert(&mut self, c: char) -> Result<()> {
+        if !c.is_ascii_lowercase() {
+            bail!("`{}` is not a lowercase answer", c);
+        }
+        *self |= 1 << (c as u8 - b'a');
+        Ok(())
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        self | other
+    }
+
+    fn intersection(&self, other: &Self) -> Self {
+        self & other
+    }
+
+    fn len(&self) -> usize {
+        self.count_ones() as usize
+    }
+}
+
+impl Answers for u128 {
+    fn empty() -> Self {
+        0
+    }
+
+    fn insert(&mut self, c: char) -> Result<()> {
+        if !c.is_ascii() {
+            bail!("`{}` does not fit in a 128 bit answer set", c);
+        }
+        *self |= 1 << c as u32;
+        Ok(())
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        self | other
+    }
+
+    fn intersection(&self, other: &Self) -> Self {
+        self & other
+    }
+
+    fn len(&self) -> usize {
+        self.count_ones() as usize
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bits256([u128; 2]);
+
+impl Answers for Bits256 {
+    fn empty() -> Self {
+        Self::default()
+    }
+
+    fn insert(&mut self, c: char) -> Result<()> {
+        let idx = c as u32;
+        if idx >= 256 {
+            bail!("`{}` does not fit in a 256 bit answer set", c);
+        }
+        self.0[(idx / 128) as usize] |= 1 << (idx % 128);
+        Ok(())
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        Self([self.0[0] | other.0[0], self.0[1] | other.0[1]])
+    }
+
+    fn intersection(&self, other: &Self) -> Self {
+        Self([self.0[0] & other.0[0], self.0[1] & other.0[1]])
+    }
+
+    fn len(&self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct DynamicBits(Vec<u64>);
+
+impl Answers for DynamicBits {
+    fn empty() -> Self {
+        Self::default()
+    }
+
+    fn insert(&mut self, c: char) -> Result<()> {
+        let idx = c as usize;
+        if self.0.len() <= idx / 64 {
+            self.0.resize(idx / 64 + 1, 0);
+        }
+        self.0[idx / 64] |= 1 << (idx % 64);
+        Ok(())
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        let (long, short) = if self.0.len() >= other.0.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut words = long.0.clone();
+        words.iter_mut().zip(&short.0).for_each(|(a, b)| *a |= b);
+        Self(words)
+    }
+
+    fn intersection(&self, other: &Self) -> Self {
+        Self(self.0.iter().zip(&other.0).map(|(a, b)| a & b).collect())
+    }
+
+    fn len(&self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
+}
+
+pub fn parse_groups<S: Answers>(input: &str) -> Result<Vec<Vec<S>>> {
+    input
+        .split("\n\n")
+        .map(|group| {
+            group
+                .lines()
+                .map(|l| {
+                    let mut set = S::empty();
+                    for c in l.chars() {
+                        set.insert(c)
+                            .with_context(|| format!("invalid answers `{}`", l))?;
+                    }
+                    Ok(set)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub fn anyone<S: Answers>(groups: &[Vec<S>]) -> usize {
+    groups
+        .iter()
+        .map(|group| group.iter().fold(S::empty(), |acc, set| acc.union(set)))
+        .map(|set| set.len())
+        .sum()
+}
+
+pub fn everyone<S: Answers>(groups: &[Vec<S>]) -> usize {
+    groups
+        .iter()
+        .filter_map(|group| {
+            let (first, rest) = group.split_first()?;
+            Some(
+                rest.iter()
+                    .fold(first.clone(), |acc, set| acc.intersection(set)),
+            )
+        })
+        .map(|set| set.len())
+        .sum()
+}
@@ -4,6 +4,10 @@ use anyhow::Result;
 
 use crate::Runner;
 
+mod answers;
+
+pub use self::answers::{anyone, everyone, parse_groups, Answers, Bits256, DynamicBits};
+
 pub struct Day06;
 pub struct Day06Slow;
 
@@ -69,19 +73,7 @@ impl Runner for Day06 {
     }
 
     fn get_input(input: &str) -> Result<Self::Input> {
-        Ok(input
-            .split("\n\n")
-            .map(|group| {
-                group
-                    .lines()
-                    .map(|l| {
-                        let mut bits = 0;
-                        l.chars().for_each(|c| bits |= 1 << (c as u8 - b'a'));
-                        bits
-                    })
-                    .collect()
-            })
-            .collect())
+        parse_groups(input)
     }
 
     fn part1(input: &Self::Input) -> Result<usize> {
@@ -132,4 +124,33 @@ mod tests {
         assert_eq!(6, Day06::part2(&input)?);
         Ok(())
     }
+
+    #[test]
+    fn alphabets() -> Result<()> {
+        let input = "aB1\n\
+                     B1\n\
+                     \n\
+                     ÿé\n\
+                     é\n\
+                     \n\
+                     λx\n\
+                     xλ☃";
+
+        assert!(Day06::get_input(input).is_err());
+        assert!(parse_groups::<u128>(input).is_err());
+        assert!(parse_groups::<Bits256>(input).is_err());
+        let err = parse_groups::<u32>("abc\nAbc").unwrap_err();
+        assert_eq!("invalid answers `Abc`", err.to_string());
+
+        let ascii = parse_groups::<u128>("aB1\nB1\n\nx!\n!")?;
+        assert_eq!((5, 3), (anyone(&ascii), everyone(&ascii)));
+        let latin = parse_groups::<Bits256>("aB1\nB1\n\nÿé\né")?;
+        assert_eq!((5, 3), (anyone(&latin), everyone(&latin)));
+        let groups = parse_groups::<DynamicBits>(input)?;
+        assert_eq!((8, 5), (anyone(&groups), everyone(&groups)));
+
+        let sample = parse_groups::<DynamicBits>("abc\n\na\nb\nc\n\nab\nac")?;
+        assert_eq!((9, 4), (anyone(&sample), everyone(&sample)));
+        Ok(())
+    }
 }
@@ -1,13 +1,14 @@
 use anyhow::Result;
 
 use crate::{
-    machine::{parse_program, Machine, Op},
+    machine::{parse_program, Compiled, Machine, Op},
     Runner,
 };
 
 pub struct Day08;
+pub struct Day08Slow;
 
-impl Runner for Day08 {
+impl Runner for Day08Slow {
     type Input = Vec<Op>;
     type Output = i32;
 
@@ -15,6 +16,10 @@ impl Runner for Day08 {
         8
     }
 
+    fn comment() -> &'static str {
+        "Slow"
+    }
+
     fn get_input(input: &str) -> Result<Self::Input> {
         let (_, ops) = parse_program(input).unwrap();
         Ok(ops)
@@ -51,26 +56,65 @@ impl Runner for Day08 {
     }
 }
 
+impl Runner for Day08 {
+    type Input = Vec<Op>;
+    type Output = i32;
+
+    fn day() -> usize {
+        8
+    }
+
+    fn comment() -> &'static str {
+        "Compiled"
+    }
+
+    fn get_input(input: &str) -> Result<Self::Input> {
+        Day08Slow::get_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Output> {
+        let mut machine = Compiled::new(input);
+
+        machine.run().unwrap();
+        Ok(machine.get())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Output> {
+        let mut machine = Compiled::new(input);
+        let acc = input
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, &op)| matches!(op, Op::Jmp(_) | Op::Nop(_)))
+            .find_map(|(idx, _)| {
+                machine.reset().flip(idx);
+                let halted = machine.run().is_ok() && machine.curr_ip() == input.len();
+                let acc = machine.get();
+                machine.flip(idx);
+                if halted {
+                    Some(acc)
+                } else {
+                    None
+                }
+            });
+
+        Ok(acc.unwrap())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::machine::SAMPLE;
 
     #[test]
     fn sample1() -> Result<()> {
-        let input = "nop +0\n\
-                     acc +1\n\
-                     jmp +4\n\
-                     acc +3\n\
-                     jmp -3\n\
-                     acc -99\n\
-                     acc +1\n\
-                     jmp -4\n\
-                     acc +6";
-
-        let input = Day08::get_input(input)?;
+        let input = Day08::get_input(SAMPLE)?;
         println!("{:#?}", input);
         assert_eq!(5, Day08::part1(&input)?);
         assert_eq!(8, Day08::part2(&input)?);
+        assert_eq!(5, Day08Slow::part1(&input)?);
+        assert_eq!(8, Day08Slow::part2(&input)?);
         Ok(())
     }
 }
@@ -0,0 +1,316 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    slice::Chunks,
+    str::FromStr,
+};
+
+use anyhow::{bail, Error, Result};
+
+pub type Coord = (usize, usize);
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Boundary {
+    #[default]
+    Clip,
+    WrapX,
+    Wrap,
+    Reflect,
+}
+
+fn reflect(i: isize, len: usize) -> usize {
+    let period = 2 * (len as isize - 1);
+    if period == 0 {
+        return 0;
+    }
+    let i = i.rem_euclid(period);
+    if i < len as isize {
+        i as usize
+    } else {
+        (period - i) as usize
+    }
+}
+
+impl Boundary {
+    pub fn resolve(self, (x, y): (isize, isize), (width, height): Coord) -> Option<Coord> {
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let clip = |i: isize, len: usize| Some(i as usize).filter(|_| i >= 0 && (i as usize) < len);
+        let (x, y) = match self {
+            Self::Clip => (clip(x, width)?, clip(y, height)?),
+            Self::WrapX => (x.rem_euclid(width as isize) as usize, clip(y, height)?),
+            Self::Wrap => (
+                x.rem_euclid(width as isize) as usize,
+                y.rem_euclid(height as isize) as usize,
+            ),
+            Self::Reflect => (reflect(x, width), reflect(y, height)),
+        };
+        Some((x, y))
+    }
+}
+
+impl FromStr for Boundary {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "clip" => Self::Clip,
+            "wrap_x" => Self::WrapX,
+            "wrap" => Self::Wrap,
+            "reflect" => Self::Reflect,
+            _ => bail!("unknown boundary `{}`", s),
+        })
+    }
+}
+
+impl Display for Boundary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Clip => "clip",
+            Self::WrapX => "wrap_x",
+            Self::Wrap => "wrap",
+            Self::Reflect => "reflect",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid2D<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+    boundary: Boundary,
+}
+
+pub const VON_NEUMANN: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+pub const MOORE: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+impl<T> Grid2D<T> {
+    pub fn parse<F>(input: &str, tile: F) -> Result<Self>
+    where
+        F: Fn(char) -> Option<T>,
+    {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::new();
+        for (number, line) in input.lines().enumerate() {
+            let start = cells.len();
+            for (column, c) in line.chars().enumerate() {
+                match tile(c) {
+                    Some(t) => cells.push(t),
+                    None => bail!(
+                        "line {}, column {}: unknown tile `{}`",
+                        number + 1,
+                        column + 1,
+                        c
+                    ),
+                }
+            }
+            let len = cells.len() - start;
+            match width {
+                None => width = Some(len),
+                Some(width) if width != len => bail!(
+                    "line {}: expected {} columns, found {}",
+                    number + 1,
+                    width,
+                    len
+                ),
+                _ => {}
+            }
+            height += 1;
+        }
+        Self::new(width.unwrap_or(0), height, cells)
+    }
+
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Result<Self> {
+        if cells.len() != width * height {
+            bail!(
+                "{} cells do not fill a {}x{} grid",
+                cells.len(),
+                width,
+                height
+            );
+        }
+        Ok(Self {
+            width,
+            height,
+            cells,
+            boundary: Boundary::Clip,
+        })
+    }
+
+    pub fn with_boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn dim(&self) -> Coord {
+        (self.width, self.height)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn index(&self, (x, y): Coord) -> usize {
+        y * self.width + x
+    }
+
+    pub fn coord(&self, idx: usize) -> Coord {
+        (idx % self.width, idx / self.width)
+    }
+
+    pub fn resolve(&self, coord: (isize, isize)) -> Option<Coord> {
+        self.boundary.resolve(coord, self.dim())
+    }
+
+    pub fn get(&self, coord: (isize, isize)) -> Option<&T> {
+        self.resolve(coord)
+            .map(|coord| &self.cells[self.index(coord)])
+    }
+
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut Vec<T> {
+        &mut self.cells
+    }
+
+    pub fn rows(&self) -> Chunks<'_, T> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn neighbors<'a>(
+        &'a self,
+        (x, y): Coord,
+        offsets: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = Coord> + 'a {
+        offsets
+            .iter()
+            .filter_map(move |&(a, b)| self.resolve((x as isize + a, y as isize + b)))
+            .filter(move |&coord| coord != (x, y))
+    }
+
+    pub fn adjacent(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        self.neighbors(coord, &VON_NEUMANN)
+    }
+
+    pub fn surrounding(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        self.neighbors(coord, &MOORE)
+    }
+
+    pub fn map<U, F: Fn(&T) -> U>(&self, f: F) -> Grid2D<U> {
+        Grid2D {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+            boundary: self.boundary,
+        }
+    }
+
+    pub fn into_cells(self) -> Vec<T> {
+        self.cells
+    }
+}
+
+impl<T: Display> Display for Grid2D<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            row.iter().try_for_each(|cell| write!(f, "{}", cell))?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> std::ops::Index<Coord> for Grid2D<T> {
+    type Output = T;
+
+    fn index(&self, coord: Coord) -> &T {
+        &self.cells[Grid2D::index(self, coord)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boundaries() {
+        let dim = (4, 3);
+        assert_eq!(None, Boundary::Clip.resolve((4, 0), dim));
+        assert_eq!(Some((3, 2)), Boundary::Clip.resolve((3, 2), dim));
+        assert_eq!(Some((1, 0)), Boundary::WrapX.resolve((9, 0), dim));
+        assert_eq!(Some((3, 1)), Boundary::WrapX.resolve((-1, 1), dim));
+        assert_eq!(None, Boundary::WrapX.resolve((0, 3), dim));
+        assert_eq!(Some((3, 2)), Boundary::Wrap.resolve((-1, -1), dim));
+        assert_eq!(Some((1, 1)), Boundary::Reflect.resolve((-1, 3), dim));
+        assert_eq!(Some((2, 0)), Boundary::Reflect.resolve((4, -4), dim));
+        assert_eq!(Some((0, 0)), Boundary::Reflect.resolve((-5, 7), (1, 1)));
+    }
+
+    #[test]
+    fn get() -> Result<()> {
+        let grid = Grid2D::new(3, 2, (0..6).collect())?;
+        assert_eq!(Some(&5), grid.get((2, 1)));
+        assert_eq!(None, grid.get((3, 1)));
+        let grid = grid.with_boundary(Boundary::Wrap);
+        assert_eq!(Some(&3), grid.get((3, 1)));
+        assert_eq!(4, grid[(1, 1)]);
+        assert!(Grid2D::new(3, 2, vec![0; 5]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse() -> Result<()> {
+        let digit = |c: char| c.to_digit(10);
+        let grid = Grid2D::parse("123\n456\n", digit)?;
+        assert_eq!((3, 2), grid.dim());
+        assert_eq!("123\n456", grid.to_string());
+        assert_eq!(
+            vec![(1, 0), (0, 1), (2, 1)],
+            grid.adjacent((1, 1)).collect::<Vec<_>>()
+        );
+        assert_eq!(5, grid.surrounding((1, 1)).count());
+        let grid = grid.with_boundary(Boundary::Wrap);
+        assert_eq!(4, grid.adjacent((0, 0)).count());
+
+        let err = Grid2D::parse("123\n45", digit).unwrap_err();
+        assert_eq!("line 2: expected 3 columns, found 2", err.to_string());
+        let err = Grid2D::parse("123\n4x6", digit).unwrap_err();
+        assert_eq!("line 2, column 2: unknown tile `x`", err.to_string());
+        Ok(())
+    }
+}
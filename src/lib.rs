@@ -6,6 +6,7 @@ use std::{
 
 use anyhow::Result;
 
+pub mod automaton;
 pub mod day1;
 pub mod day10;
 pub mod day11;
@@ -23,8 +24,22 @@ pub mod day6;
 pub mod day7;
 pub mod day8;
 pub mod day9;
+pub mod grid;
 pub mod machine;
 
+#[cfg(test)]
+pub(crate) struct Lcg(pub u32);
+
+#[cfg(test)]
+impl Iterator for Lcg {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        self.0 = self.0.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        Some(self.0 >> 16)
+    }
+}
+
 pub fn run() -> Result<Duration> {
     let mut total_time = day1::Day01::run()?;
     total_time += day2::Day02::run()?;
@@ -37,9 +52,11 @@ pub fn run() -> Result<Duration> {
     total_time += day7::Day07::run()?;
     // day7::Day07Dag::run()?;
     total_time += day8::Day08::run()?;
+    // day8::Day08Slow::run()?;
     total_time += day9::Day09::run()?;
     total_time += day10::Day10::run()?;
     day11::Day11::run()?;
+    day11::Day11Automaton::run()?;
     total_time += day11::Day11Unsafe::run()?;
     total_time += day12::Day12::run()?;
     total_time += day13::Day13::run()?;
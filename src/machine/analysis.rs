@@ -0,0 +1,291 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display, Formatter},
+};
+
+use itertools::Itertools;
+
+use super::Op;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    Block(usize),
+    Halt,
+    OutOfRange(i64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub start: usize,
+    pub end: usize,
+    pub acc: i32,
+    pub exit: Exit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Halts,
+    Escapes { ip: usize, target: i64 },
+    Loops { path: Vec<usize>, cycle: Vec<usize> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis {
+    pub blocks: Vec<Block>,
+    pub unreachable: Vec<usize>,
+    pub escapes: Vec<(usize, i64)>,
+    pub cycles: Vec<Vec<usize>>,
+    pub outcome: Outcome,
+}
+
+impl Analysis {
+    pub fn cycle_acc(&self, cycle: &[usize]) -> i32 {
+        cycle.iter().map(|&b| self.blocks[b].acc).sum()
+    }
+}
+
+fn target(ip: usize, offset: i32) -> i64 {
+    ip as i64 + offset as i64
+}
+
+fn find_blocks(ops: &[Op]) -> Vec<Block> {
+    let len = ops.len() as i64;
+    let mut leaders = BTreeSet::new();
+    if !ops.is_empty() {
+        leaders.insert(0);
+    }
+    ops.iter().enumerate().for_each(|(ip, op)| {
+        if let Op::Jmp(i) = op {
+            let t = target(ip, *i);
+            if (0..len).contains(&t) {
+                leaders.insert(t as usize);
+            }
+            if ip + 1 < ops.len() {
+                leaders.insert(ip + 1);
+            }
+        }
+    });
+
+    let bounds = leaders
+        .iter()
+        .copied()
+        .chain(std::iter::once(ops.len()))
+        .tuple_windows()
+        .collect::<Vec<(usize, usize)>>();
+    let mut block_of = vec![0; ops.len()];
+    bounds.iter().enumerate().for_each(|(b, &(start, end))| {
+        block_of[start..end].iter_mut().for_each(|x| *x = b);
+    });
+
+    bounds
+        .into_iter()
+        .map(|(start, end)| {
+            let acc = ops[start..end]
+                .iter()
+                .map(|op| match op {
+                    Op::Acc(i) => *i,
+                    _ => 0,
+                })
+                .sum();
+            let next = match ops[end - 1] {
+                Op::Jmp(i) => target(end - 1, i),
+                _ => end as i64,
+            };
+            let exit = if (0..len).contains(&next) {
+                Exit::Block(block_of[next as usize])
+            } else if next == len {
+                Exit::Halt
+            } else {
+                Exit::OutOfRange(next)
+            };
+            Block {
+                start,
+                end,
+                acc,
+                exit,
+            }
+        })
+        .collect()
+}
+
+fn find_cycles(blocks: &[Block]) -> Vec<Vec<usize>> {
+    // Every block has exactly one successor, so each walk ends in an exit or a single cycle.
+    let mut state = vec![0u8; blocks.len()];
+    let mut cycles = Vec::new();
+    for start in 0..blocks.len() {
+        let mut path = Vec::new();
+        let mut b = Some(start);
+        while let Some(next) = b.filter(|&next| state[next] == 0) {
+            state[next] = 1;
+            path.push(next);
+            b = match blocks[next].exit {
+                Exit::Block(next) => Some(next),
+                _ => None,
+            };
+        }
+        if let Some(idx) = b.and_then(|b| path.iter().position(|&p| p == b)) {
+            cycles.push(path[idx..].to_vec());
+        }
+        path.iter().for_each(|&p| state[p] = 2);
+    }
+    cycles
+}
+
+pub fn analyze(ops: &[Op]) -> Analysis {
+    let blocks = find_blocks(ops);
+    let cycles = find_cycles(&blocks);
+
+    let mut path: Vec<usize> = Vec::new();
+    let outcome = if blocks.is_empty() {
+        Outcome::Halts
+    } else {
+        let mut b = 0;
+        loop {
+            if let Some(idx) = path.iter().position(|&p| p == b) {
+                break Outcome::Loops {
+                    cycle: path[idx..].to_vec(),
+                    path: path.clone(),
+                };
+            }
+            path.push(b);
+            match blocks[b].exit {
+                Exit::Block(next) => b = next,
+                Exit::Halt => break Outcome::Halts,
+                Exit::OutOfRange(target) => {
+                    break Outcome::Escapes {
+                        ip: blocks[b].end - 1,
+                        target,
+                    }
+                }
+            }
+        }
+    };
+
+    let reachable = path.iter().copied().collect::<BTreeSet<_>>();
+    let unreachable = blocks
+        .iter()
+        .enumerate()
+        .filter(|(b, _)| !reachable.contains(b))
+        .flat_map(|(_, block)| block.start..block.end)
+        .collect();
+    let escapes = ops
+        .iter()
+        .enumerate()
+        .filter_map(|(ip, op)| match op {
+            Op::Jmp(i) if !(0..=ops.len() as i64).contains(&target(ip, *i)) => {
+                Some((ip, target(ip, *i)))
+            }
+            _ => None,
+        })
+        .collect();
+
+    Analysis {
+        blocks,
+        unreachable,
+        escapes,
+        cycles,
+        outcome,
+    }
+}
+
+impl Display for Exit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Exit::Block(b) => write!(f, "B{}", b),
+            Exit::Halt => f.write_str("halt"),
+            Exit::OutOfRange(t) => write!(f, "out of range ({})", t),
+        }
+    }
+}
+
+impl Display for Analysis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let blocks = |bs: &[usize]| bs.iter().map(|b| format!("B{}", b)).join(" -> ");
+        writeln!(f, "blocks:")?;
+        for (b, block) in self.blocks.iter().enumerate() {
+            writeln!(
+                f,
+                "  B{} [{}..{}) acc {:+} -> {}",
+                b, block.start, block.end, block.acc, block.exit
+            )?;
+        }
+        if !self.unreachable.is_empty() {
+            writeln!(f, "unreachable: {}", self.unreachable.iter().join(", "))?;
+        }
+        for (ip, target) in &self.escapes {
+            writeln!(f, "jump at {} leaves the program ({})", ip, target)?;
+        }
+        for cycle in &self.cycles {
+            writeln!(
+                f,
+                "loop: {} (acc {:+} per iteration)",
+                blocks(cycle),
+                self.cycle_acc(cycle)
+            )?;
+        }
+        match &self.outcome {
+            Outcome::Halts => write!(f, "program halts"),
+            Outcome::Escapes { ip, target } => {
+                write!(f, "program jumps out of range at {} ({})", ip, target)
+            }
+            Outcome::Loops { path, cycle } => {
+                let closing = self.blocks[*cycle.last().unwrap()].end - 1;
+                write!(
+                    f,
+                    "program loops forever: {}, closed by the jump at {}",
+                    blocks(path),
+                    closing
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine::{assemble, SAMPLE};
+
+    #[test]
+    fn sample1() -> anyhow::Result<()> {
+        let ops = assemble(SAMPLE)?;
+        let analysis = analyze(&ops);
+        assert_eq!(
+            vec![(0, 1), (1, 3), (3, 5), (5, 6), (6, 8), (8, 9)],
+            analysis
+                .blocks
+                .iter()
+                .map(|b| (b.start, b.end))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Exit::Halt, analysis.blocks[5].exit);
+        assert_eq!(vec![5, 8], analysis.unreachable);
+        assert!(analysis.escapes.is_empty());
+        assert_eq!(vec![vec![1, 4, 2]], analysis.cycles);
+        assert_eq!(5, analysis.cycle_acc(&analysis.cycles[0]));
+        assert_eq!(
+            Outcome::Loops {
+                path: vec![0, 1, 4, 2],
+                cycle: vec![1, 4, 2]
+            },
+            analysis.outcome
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn exits() -> anyhow::Result<()> {
+        let analysis = analyze(&assemble("acc +1\njmp +2\nacc +2\nacc +3")?);
+        assert_eq!(Outcome::Halts, analysis.outcome);
+        assert_eq!(vec![2], analysis.unreachable);
+
+        let analysis = analyze(&assemble("acc +1\njmp +1")?);
+        assert!(analysis.escapes.is_empty());
+        assert_eq!(Outcome::Halts, analysis.outcome);
+
+        let analysis = analyze(&assemble("acc +1\njmp -5")?);
+        assert_eq!(vec![(1, -4)], analysis.escapes);
+        assert_eq!(Outcome::Escapes { ip: 1, target: -4 }, analysis.outcome);
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+
+use super::Op;
+
+#[derive(Debug, Clone, Copy)]
+enum Operand<'a> {
+    Offset(i32),
+    Label(&'a str),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Line<'a> {
+    number: usize,
+    code: &'a str,
+    operand: Operand<'a>,
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn strip_comment(line: &str) -> &str {
+    match line.find(&[';', '#'][..]) {
+        Some(idx) => &line[..idx],
+        None => line,
+    }
+}
+
+fn split_label(line: &str) -> Option<(&str, &str)> {
+    let idx = line.find(':')?;
+    let label = line[..idx].trim();
+    if is_ident(label) {
+        Some((label, line[idx + 1..].trim()))
+    } else {
+        None
+    }
+}
+
+fn parse_operand(number: usize, operand: &str) -> Result<Operand<'_>> {
+    if operand.starts_with(|c: char| c == '+' || c == '-' || c.is_ascii_digit()) {
+        match operand.trim_start_matches('+').parse() {
+            Ok(i) => Ok(Operand::Offset(i)),
+            Err(_) => bail!("line {}: invalid offset `{}`", number, operand),
+        }
+    } else if is_ident(operand) {
+        Ok(Operand::Label(operand))
+    } else {
+        bail!("line {}: invalid operand `{}`", number, operand)
+    }
+}
+
+pub fn assemble(input: &str) -> Result<Vec<Op>> {
+    let mut labels: HashMap<&str, (usize, usize)> = HashMap::new();
+    let mut lines = Vec::new();
+    for (number, line) in input.lines().enumerate() {
+        let number = number + 1;
+        let mut line = strip_comment(line).trim();
+        while let Some((label, rest)) = split_label(line) {
+            if let Some((_, first)) = labels.insert(label, (lines.len(), number)) {
+                bail!(
+                    "line {}: duplicate label `{}` (first defined on line {})",
+                    number,
+                    label,
+                    first
+                );
+            }
+            line = rest;
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        let code = parts.next().unwrap();
+        let operand = match (parts.next(), parts.next()) {
+            (Some(operand), None) => parse_operand(number, operand)?,
+            (None, _) => bail!("line {}: `{}` is missing an operand", number, code),
+            (Some(_), Some(extra)) => bail!("line {}: unexpected `{}`", number, extra),
+        };
+        lines.push(Line {
+            number,
+            code,
+            operand,
+        });
+    }
+
+    lines
+        .iter()
+        .enumerate()
+        .map(|(ip, line)| {
+            let offset = match line.operand {
+                Operand::Offset(i) => i,
+                Operand::Label(label) => match labels.get(label) {
+                    Some(&(target, _)) => target as i32 - ip as i32,
+                    None => bail!("line {}: undefined label `{}`", line.number, label),
+                },
+            };
+            Ok(match (line.code, line.operand) {
+                ("nop", _) => Op::Nop(offset),
+                ("jmp", _) => Op::Jmp(offset),
+                ("acc", Operand::Offset(_)) => Op::Acc(offset),
+                ("acc", Operand::Label(label)) => {
+                    bail!("line {}: `acc` cannot take label `{}`", line.number, label)
+                }
+                (code, _) => bail!("line {}: unknown instruction `{}`", line.number, code),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine::{parse_program, Listing, Machine, SAMPLE};
+
+    #[test]
+    fn labels() -> Result<()> {
+        let input = "; Day 8 sample, with labels\n\
+                     nop +0\n\
+                     top: acc +1\n\
+                     jmp skip\n\
+                     back:\n\
+                     acc +3\n\
+                     jmp top  # loops forever\n\
+                     acc -99\n\
+                     skip: acc +1\n\
+                     jmp back\n\
+                     acc +6";
+        let ops = assemble(input)?;
+        let (_, expected) = parse_program(SAMPLE).unwrap();
+        assert_eq!(expected, ops);
+
+        let mut machine = Machine::new(&ops);
+        machine.run().unwrap();
+        assert_eq!(5, machine.get());
+
+        let listing = Listing::new(&ops).with_labels().to_string();
+        assert_eq!(ops, assemble(&listing)?);
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        let err = assemble("nop +0\njmp nowhere").unwrap_err();
+        assert_eq!("line 2: undefined label `nowhere`", err.to_string());
+
+        let err = assemble("a: nop +0\n\na: jmp a").unwrap_err();
+        assert_eq!(
+            "line 3: duplicate label `a` (first defined on line 1)",
+            err.to_string()
+        );
+
+        let err = assemble("nop +0\nmul +2").unwrap_err();
+        assert_eq!("line 2: unknown instruction `mul`", err.to_string());
+    }
+}
@@ -0,0 +1,138 @@
+use super::Op;
+
+const OUT_OF_RANGE: usize = usize::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Decoded {
+    Acc(i32),
+    Jmp(usize),
+    Nop(usize),
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct Compiled {
+    ops: Vec<Decoded>,
+    visited: Vec<u64>,
+    ip: usize,
+    acc: i32,
+}
+
+impl Compiled {
+    pub fn new(ops: &[Op]) -> Self {
+        let ops = ops
+            .iter()
+            .enumerate()
+            .map(|(ip, op)| {
+                let target = |i: i32| {
+                    let target = ip as i64 + i as i64;
+                    if target < 0 {
+                        OUT_OF_RANGE
+                    } else {
+                        target as usize
+                    }
+                };
+                match *op {
+                    Op::Acc(i) => Decoded::Acc(i),
+                    Op::Jmp(i) => Decoded::Jmp(target(i)),
+                    Op::Nop(i) => Decoded::Nop(target(i)),
+                }
+            })
+            .collect::<Vec<_>>();
+        Self {
+            visited: vec![0; ops.len().div_ceil(64)],
+            ops,
+            ..Default::default()
+        }
+    }
+
+    pub fn reset(&mut self) -> &mut Self {
+        self.visited.iter_mut().for_each(|word| *word = 0);
+        self.ip = 0;
+        self.acc = 0;
+        self
+    }
+
+    #[inline]
+    fn seen(&self, ip: usize) -> bool {
+        self.visited[ip >> 6] & (1 << (ip & 63)) != 0
+    }
+
+    #[inline]
+    pub fn step(&mut self) -> Result<(), &'static str> {
+        self.visited[self.ip >> 6] |= 1 << (self.ip & 63);
+        match self.ops[self.ip] {
+            Decoded::Nop(_) => self.ip += 1,
+            Decoded::Acc(i) => {
+                self.acc += i;
+                self.ip += 1;
+            }
+            Decoded::Jmp(OUT_OF_RANGE) => return Err("Out of range"),
+            Decoded::Jmp(target) => self.ip = target,
+        }
+        Ok(())
+    }
+
+    pub fn run(&mut self) -> Result<&Self, &Self> {
+        while self.ip < self.ops.len() && !self.seen(self.ip) {
+            if self.step().is_err() {
+                return Err(self);
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn flip(&mut self, ip: usize) -> &Self {
+        self.ops[ip] = match self.ops[ip] {
+            Decoded::Nop(target) => Decoded::Jmp(target),
+            Decoded::Jmp(target) => Decoded::Nop(target),
+            op => op,
+        };
+        self
+    }
+
+    pub fn get(&self) -> i32 {
+        self.acc
+    }
+
+    pub fn curr_ip(&self) -> usize {
+        self.ip
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine::{assemble, Machine, SAMPLE};
+
+    #[test]
+    fn matches_machine() -> anyhow::Result<()> {
+        let ops = assemble(SAMPLE)?;
+        let mut compiled = Compiled::new(&ops);
+        for ip in 0..ops.len() {
+            let mut machine = Machine::new(&ops);
+            machine.flip(ip);
+            let expected = machine.run().is_ok();
+
+            compiled.reset().flip(ip);
+            assert_eq!(expected, compiled.run().is_ok());
+            assert_eq!(
+                (machine.curr_ip(), machine.get()),
+                (compiled.curr_ip(), compiled.get())
+            );
+            compiled.flip(ip);
+        }
+
+        let mut compiled = Compiled::new(&assemble("acc +2\njmp -2")?);
+        assert!(compiled.run().is_err());
+        assert_eq!(2, compiled.get());
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Display, Formatter},
+};
+
+use super::Op;
+
+impl Display for Op {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Op::Acc(i) => write!(f, "acc {:+}", i),
+            Op::Jmp(i) => write!(f, "jmp {:+}", i),
+            Op::Nop(i) => write!(f, "nop {:+}", i),
+        }
+    }
+}
+
+pub fn format_program(ops: &[Op]) -> String {
+    Listing::new(ops).to_string()
+}
+
+fn target(ip: usize, offset: i32) -> Option<usize> {
+    let target = ip as i64 + offset as i64;
+    if target < 0 {
+        None
+    } else {
+        Some(target as usize)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Listing<'a> {
+    ops: &'a [Op],
+    indices: bool,
+    labels: bool,
+    visited: Option<Vec<bool>>,
+}
+
+impl<'a> Listing<'a> {
+    pub fn new(ops: &'a [Op]) -> Self {
+        Self {
+            ops,
+            indices: false,
+            labels: false,
+            visited: None,
+        }
+    }
+
+    pub fn with_indices(mut self) -> Self {
+        self.indices = true;
+        self
+    }
+
+    pub fn with_labels(mut self) -> Self {
+        self.labels = true;
+        self
+    }
+
+    pub fn with_visited<I>(mut self, visited: I) -> Self
+    where
+        I: IntoIterator<Item = bool>,
+    {
+        self.visited = Some(visited.into_iter().collect());
+        self
+    }
+
+    fn targets(&self) -> HashSet<usize> {
+        self.ops
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, op)| match op {
+                Op::Jmp(i) => target(idx, *i),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl<'a> Display for Listing<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let width = self.ops.len().to_string().len();
+        let targets = if self.labels {
+            self.targets()
+        } else {
+            HashSet::new()
+        };
+        for (ip, op) in self.ops.iter().enumerate() {
+            if ip > 0 {
+                writeln!(f)?;
+            }
+            if let Some(visited) = &self.visited {
+                let seen = visited.get(ip).copied().unwrap_or(false);
+                f.write_str(if seen { "* " } else { "  " })?;
+            }
+            if self.indices {
+                write!(f, "{:>width$}  ", ip, width = width)?;
+            }
+            if self.labels {
+                if targets.contains(&ip) {
+                    write!(f, "{:<width$}", format!("L{}:", ip), width = width + 3)?;
+                } else {
+                    write!(f, "{:<width$}", "", width = width + 3)?;
+                }
+            }
+            write!(f, "{}", op)?;
+            if self.labels {
+                if let Op::Jmp(i) = op {
+                    match target(ip, *i) {
+                        Some(t) if t <= self.ops.len() => write!(f, "  ; -> L{}", t)?,
+                        _ => f.write_str("  ; -> out of range")?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine::{parse_program, Machine, SAMPLE};
+
+    #[test]
+    fn round_trip() {
+        let (_, ops) = parse_program(SAMPLE).unwrap();
+        let text = format_program(&ops);
+        assert_eq!(SAMPLE, text);
+        let (rest, again) = parse_program(&text).unwrap();
+        assert_eq!("", rest);
+        assert_eq!(ops, again);
+    }
+
+    #[test]
+    fn annotated() {
+        let (_, ops) = parse_program(SAMPLE).unwrap();
+        let mut machine = Machine::new(&ops);
+        machine.run().unwrap();
+        let listing = Listing::new(&ops)
+            .with_indices()
+            .with_labels()
+            .with_visited(machine.visited())
+            .to_string();
+        let lines = listing.lines().collect::<Vec<_>>();
+        assert_eq!("* 0      nop +0", lines[0]);
+        assert_eq!("* 1  L1: acc +1", lines[1]);
+        assert_eq!("* 2      jmp +4  ; -> L6", lines[2]);
+        assert_eq!("  5      acc -99", lines[5]);
+        assert_eq!("* 7      jmp -4  ; -> L3", lines[7]);
+    }
+}
@@ -1,22 +1,76 @@
+pub mod analysis;
+mod asm;
+mod compiled;
+mod format;
 mod parse;
 
+pub use self::asm::assemble;
+pub use self::compiled::Compiled;
+pub use self::format::{format_program, Listing};
 pub(crate) use self::parse::parse_program;
 
+use std::collections::HashSet;
+
+pub const DEFAULT_BUDGET: usize = 1 << 20;
+
+#[cfg(test)]
+pub(crate) const SAMPLE: &str = "nop +0\n\
+                                 acc +1\n\
+                                 jmp +4\n\
+                                 acc +3\n\
+                                 jmp -3\n\
+                                 acc -99\n\
+                                 acc +1\n\
+                                 jmp -4\n\
+                                 acc +6";
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopDetection {
+    #[default]
+    Visited,
+    StateHash,
+    Disabled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Running,
+    Halted,
+    Looped,
+    Fault,
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct Machine {
     instructions: Vec<(Op, bool)>,
     ip: usize,
     acc: i32,
+    steps: usize,
+    fault: bool,
+    detection: LoopDetection,
+    states: HashSet<(usize, i32)>,
+    budget: usize,
 }
 
 impl Machine {
     pub fn new(ops: &[Op]) -> Self {
         Self {
             instructions: ops.iter().map(|op| (*op, false)).collect(),
+            budget: DEFAULT_BUDGET,
             ..Default::default()
         }
     }
 
+    pub fn with_detection(mut self, detection: LoopDetection) -> Self {
+        self.detection = detection;
+        self
+    }
+
+    pub fn with_budget(mut self, budget: usize) -> Self {
+        self.budget = budget;
+        self
+    }
+
     pub fn step(&mut self) -> Result<(), &'static str> {
         let op = self.instructions[self.ip];
         self.instructions[self.ip].1 = true;
@@ -37,13 +91,49 @@ impl Machine {
     }
 
     pub fn run(&mut self) -> Result<&Self, &Self> {
-        while self.ip < self.instructions.len() && !self.instructions[self.ip].1 {
+        // Every instruction can only be visited once before a loop is caught
+        let budget = match self.detection {
+            LoopDetection::Visited => self.instructions.len() + 1,
+            _ => self.budget,
+        };
+        match self.run_for(budget) {
+            Status::Halted | Status::Looped => Ok(self),
+            Status::Running | Status::Fault => Err(self),
+        }
+    }
+
+    pub fn run_for(&mut self, steps: usize) -> Status {
+        for _ in 0..steps {
+            match self.status() {
+                Status::Running => (),
+                status => return status,
+            }
+            if self.detection == LoopDetection::StateHash {
+                self.states.insert((self.ip, self.acc));
+            }
             if self.step().is_err() {
-                return Err(self);
+                self.fault = true;
+                return Status::Fault;
             }
+            self.steps += 1;
         }
+        self.status()
+    }
 
-        Ok(self)
+    pub fn status(&self) -> Status {
+        if self.fault {
+            return Status::Fault;
+        }
+        if self.ip >= self.instructions.len() {
+            return Status::Halted;
+        }
+        match self.detection {
+            LoopDetection::Visited if self.instructions[self.ip].1 => Status::Looped,
+            LoopDetection::StateHash if self.states.contains(&(self.ip, self.acc)) => {
+                Status::Looped
+            }
+            _ => Status::Running,
+        }
     }
 
     pub fn flip(&mut self, ip: usize) -> &Self {
@@ -62,11 +152,76 @@ impl Machine {
     pub fn curr_ip(&self) -> usize {
         self.ip
     }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn visited(&self) -> impl Iterator<Item = bool> + '_ {
+        self.instructions.iter().map(|(_, visited)| *visited)
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Op {
     Acc(i32),
     Jmp(i32),
     Nop(i32),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resumable() -> anyhow::Result<()> {
+        let ops = assemble(SAMPLE)?;
+        let mut machine = Machine::new(&ops);
+        assert_eq!(Status::Running, machine.run_for(3));
+        assert_eq!(
+            (6, 1, 3),
+            (machine.curr_ip(), machine.get(), machine.steps())
+        );
+        assert_eq!(Status::Looped, machine.run_for(100));
+        assert_eq!(5, machine.get());
+        assert_eq!(Status::Looped, machine.run_for(100));
+        assert_eq!(7, machine.steps());
+
+        let mut machine = Machine::new(&ops).with_detection(LoopDetection::Disabled);
+        assert_eq!(Status::Running, machine.run_for(1000));
+        assert_eq!(1000, machine.steps());
+
+        let mut fixed = Machine::new(&ops);
+        fixed.flip(7);
+        assert_eq!(Status::Running, fixed.run_for(4));
+        assert_eq!(Status::Halted, fixed.run_for(4));
+        assert_eq!(8, fixed.get());
+        Ok(())
+    }
+
+    #[test]
+    fn state_hash() -> anyhow::Result<()> {
+        let ops = assemble("top: nop +0\njmp top")?;
+        let mut machine = Machine::new(&ops).with_detection(LoopDetection::StateHash);
+        assert_eq!(Status::Looped, machine.run_for(10));
+        assert_eq!(2, machine.steps());
+
+        let ops = assemble("top: acc +1\njmp top")?;
+        let mut machine = Machine::new(&ops).with_detection(LoopDetection::StateHash);
+        assert_eq!(Status::Running, machine.run_for(10));
+        let mut machine = Machine::new(&ops)
+            .with_detection(LoopDetection::StateHash)
+            .with_budget(100);
+        assert!(machine.run().is_err());
+        assert_eq!((Status::Running, 100), (machine.status(), machine.steps()));
+        let mut machine = Machine::new(&ops)
+            .with_detection(LoopDetection::Disabled)
+            .with_budget(100);
+        assert!(machine.run().is_err());
+
+        let mut machine = Machine::new(&assemble("jmp -1")?);
+        assert_eq!(Status::Fault, machine.run_for(10));
+        assert!(machine.run().is_err());
+        Ok(())
+    }
+}
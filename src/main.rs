@@ -1,4 +1,7 @@
+use std::path::Path;
+
 use anyhow::Result;
+use aoc2020::day11::Export;
 use fern::colors::Color;
 use fern::colors::ColoredLevelConfig;
 
@@ -26,6 +29,25 @@ fn setup_logger() -> Result<()> {
 
 fn main() -> Result<()> {
     setup_logger()?;
+    let mut args = std::env::args().skip(1);
+    if let Some("day11") = args.next().as_deref() {
+        let rules = args.next().unwrap_or_default().parse()?;
+        let format = match args.next().as_deref() {
+            Some("ascii") => Some(Export::Ascii),
+            Some("ppm") => Some(Export::Ppm { scale: 4 }),
+            Some("gif") => Some(Export::Gif {
+                scale: 4,
+                delay: 10,
+            }),
+            Some(other) => anyhow::bail!("unknown export format `{}`", other),
+            None => None,
+        };
+        let dir = args.next().unwrap_or_else(|| "frames".to_owned());
+        let export_to = format.map(|format| (Path::new(&dir), format));
+        let occupied = aoc2020::day11::run_rules(&rules, export_to)?;
+        log::info!("Day 11 with {}: {} occupied", rules, occupied);
+        return Ok(());
+    }
     let time = aoc2020::run()?;
     log::info!("Total Time: {:?}", time);
 